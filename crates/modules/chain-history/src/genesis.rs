@@ -0,0 +1,29 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{Context, WorkingSet};
+
+use crate::ChainHistory;
+
+/// Config for the [`ChainHistory`] module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHistoryConfig {
+    /// The number of most recent slots kept in state.
+    pub window_size: u64,
+}
+
+impl<C: Context> ChainHistory<C> {
+    pub(crate) fn init_module(
+        &self,
+        config: &<Self as sov_modules_api::Module>::Config,
+        working_set: &mut WorkingSet<C>,
+    ) -> Result<()> {
+        if config.window_size == 0 {
+            bail!("The chain history window size must be at least 1");
+        }
+
+        self.window_size.set(&config.window_size, working_set);
+        self.current_height.set(&0, working_set);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use sov_modules_api::{BlockHeaderTrait, Context, DaSpec, Spec, WorkingSet};
+use sov_state::Storage;
+
+use crate::{ChainHistory, SlotRecord};
+
+impl<C: Context> ChainHistory<C> {
+    /// Records the slot about to be executed, and evicts the slot that falls out of the window.
+    pub fn begin_slot_hook<Da: DaSpec>(
+        &self,
+        slot_header: &Da::BlockHeader,
+        pre_state_root: &<<C as Spec>::Storage as Storage>::Root,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        let height = self.get_current_height(working_set) + 1;
+        let record = SlotRecord {
+            da_block_hash: slot_header.hash().into(),
+            da_height: slot_header.height(),
+            timestamp: slot_header.time().secs(),
+            pre_state_root: pre_state_root.clone().into(),
+        };
+
+        self.slots.set(&height, &record, working_set);
+        self.current_height.set(&height, working_set);
+
+        let window_size = self.get_window_size(working_set);
+        if height > window_size {
+            self.slots.delete(&(height - window_size), working_set);
+        }
+    }
+}
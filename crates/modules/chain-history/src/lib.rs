@@ -0,0 +1,103 @@
+#![deny(missing_docs)]
+//! The `chain-history` module records, for each rollup slot, the DA block it was produced from
+//! and the state root it started from.
+//!
+//! Only the most recent `window_size` slots are kept in state, so that the history does not
+//! grow without bound.
+
+mod genesis;
+mod hooks;
+#[cfg(feature = "native")]
+mod query;
+
+pub use genesis::*;
+#[cfg(feature = "native")]
+pub use query::*;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{CallResponse, Context, Error, ModuleInfo, StateMap, StateValue, WorkingSet};
+
+/// What the rollup knows about the DA block a slot was produced from.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct SlotRecord {
+    /// The hash of the DA block.
+    pub da_block_hash: [u8; 32],
+    /// The height of the DA block.
+    pub da_height: u64,
+    /// The timestamp of the DA block, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The state root of the rollup before the slot was executed.
+    ///
+    /// The state root after slot `N` is the pre-state root of slot `N + 1`.
+    pub pre_state_root: [u8; 32],
+}
+
+/// The chain history module.
+#[derive(Clone, ModuleInfo)]
+pub struct ChainHistory<C: Context> {
+    /// The address of the module.
+    #[address]
+    pub(crate) address: C::Address,
+
+    /// The number of slots kept in state.
+    #[state]
+    pub(crate) window_size: StateValue<u64>,
+
+    /// The rollup height of the last recorded slot.
+    #[state]
+    pub(crate) current_height: StateValue<u64>,
+
+    /// The recorded slots, keyed by rollup height.
+    #[state]
+    pub(crate) slots: StateMap<u64, SlotRecord>,
+}
+
+impl<C: Context> sov_modules_api::Module for ChainHistory<C> {
+    type Context = C;
+
+    type Config = ChainHistoryConfig;
+
+    type CallMessage = ();
+
+    type Event = ();
+
+    fn genesis(&self, config: &Self::Config, working_set: &mut WorkingSet<C>) -> Result<(), Error> {
+        Ok(self.init_module(config, working_set)?)
+    }
+
+    fn call(
+        &self,
+        _msg: Self::CallMessage,
+        _context: &Self::Context,
+        _working_set: &mut WorkingSet<C>,
+    ) -> Result<CallResponse, Error> {
+        Ok(CallResponse::default())
+    }
+}
+
+impl<C: Context> ChainHistory<C> {
+    /// Returns the record of the slot at the given rollup height, if it is still in the window.
+    pub fn get_slot(&self, height: u64, working_set: &mut WorkingSet<C>) -> Option<SlotRecord> {
+        self.slots.get(&height, working_set)
+    }
+
+    /// Returns the rollup height of the last recorded slot, or 0 before the first slot.
+    pub fn get_current_height(&self, working_set: &mut WorkingSet<C>) -> u64 {
+        self.current_height.get(working_set).unwrap_or_default()
+    }
+
+    /// Returns the number of slots kept in state.
+    pub fn get_window_size(&self, working_set: &mut WorkingSet<C>) -> u64 {
+        self.window_size
+            .get(working_set)
+            .expect("Window size must be set at genesis")
+    }
+}
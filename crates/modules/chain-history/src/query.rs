@@ -0,0 +1,62 @@
+//! Defines rpc queries exposed by the chain history module, along with the relevant types
+use jsonrpsee::core::RpcResult;
+use sov_modules_api::macros::rpc_gen;
+use sov_modules_api::{Context, WorkingSet};
+
+use crate::{ChainHistory, SlotRecord};
+
+/// The response type to the `chainHistory_getSlot` and `chainHistory_getLatestSlot` RPC methods.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SlotResponse {
+    /// The rollup height of the slot.
+    pub height: u64,
+    /// The record of the slot, or `None` if the slot is not in the window.
+    pub slot: Option<SlotRecord>,
+}
+
+/// The response type to the `chainHistory_getWindow` RPC method.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WindowResponse {
+    /// The lowest rollup height still recorded, or `None` before the first slot.
+    pub first_height: Option<u64>,
+    /// The rollup height of the last recorded slot, or 0 before the first slot.
+    pub current_height: u64,
+    /// The number of slots kept in state.
+    pub window_size: u64,
+}
+
+#[rpc_gen(client, server, namespace = "chainHistory")]
+impl<C: Context> ChainHistory<C> {
+    /// Returns the DA block that produced the slot at the given rollup height.
+    #[rpc_method(name = "getSlot")]
+    pub fn slot(&self, height: u64, working_set: &mut WorkingSet<C>) -> RpcResult<SlotResponse> {
+        Ok(SlotResponse {
+            height,
+            slot: self.get_slot(height, working_set),
+        })
+    }
+
+    /// Returns the last recorded slot.
+    #[rpc_method(name = "getLatestSlot")]
+    pub fn latest_slot(&self, working_set: &mut WorkingSet<C>) -> RpcResult<SlotResponse> {
+        let height = self.get_current_height(working_set);
+        Ok(SlotResponse {
+            height,
+            slot: self.get_slot(height, working_set),
+        })
+    }
+
+    /// Returns the range of rollup heights currently recorded.
+    #[rpc_method(name = "getWindow")]
+    pub fn window(&self, working_set: &mut WorkingSet<C>) -> RpcResult<WindowResponse> {
+        let current_height = self.get_current_height(working_set);
+        let window_size = self.get_window_size(working_set);
+        let first_height =
+            (current_height > 0).then(|| current_height.saturating_sub(window_size - 1).max(1));
+        Ok(WindowResponse {
+            first_height,
+            current_height,
+            window_size,
+        })
+    }
+}
@@ -0,0 +1,282 @@
+use borsh::BorshSerialize;
+use sov_bank::{Bank, Coins};
+use sov_modules_api::digest::Digest;
+use sov_modules_api::transaction::Transaction;
+use sov_modules_api::{AccessoryWorkingSet, Context, Spec, WorkingSet};
+
+use crate::{Indexer, TransferRecord, TxRecord};
+
+/// A change of token balances caused by a transaction, as seen by the indexer.
+#[derive(Debug, Clone, PartialEq, Eq, borsh::BorshDeserialize, borsh::BorshSerialize)]
+pub enum TokenEvent<C: Context> {
+    /// Tokens were moved from one address to another one.
+    ///
+    /// A transfer to the sender changes no balance, so whether it took effect cannot be told and
+    /// it is not an event.
+    Transfer {
+        /// The sender of the tokens.
+        from: C::Address,
+        /// The recipient of the tokens.
+        to: C::Address,
+        /// The transferred tokens.
+        coins: Coins<C>,
+    },
+    /// Tokens were created and credited to an address.
+    Mint {
+        /// The recipient of the tokens.
+        to: C::Address,
+        /// The minted tokens.
+        coins: Coins<C>,
+    },
+}
+
+impl<C: Context> TokenEvent<C> {
+    /// The address whose balance the event credits, and the credited tokens.
+    fn credited(&self) -> (&C::Address, &Coins<C>) {
+        match self {
+            TokenEvent::Transfer { to, coins, .. } | TokenEvent::Mint { to, coins } => (to, coins),
+        }
+    }
+}
+
+/// A token event of the transaction being executed, with the balance of the credited address
+/// before the transaction.
+#[derive(Debug, Clone, PartialEq, Eq, borsh::BorshDeserialize, borsh::BorshSerialize)]
+pub struct ExpectedEvent<C: Context> {
+    event: TokenEvent<C>,
+    balance_before: Option<u64>,
+}
+
+/// A transaction waiting for its slot to be finalized.
+#[derive(Debug, Clone, PartialEq, Eq, borsh::BorshDeserialize, borsh::BorshSerialize)]
+pub struct PendingTx<C: Context> {
+    sender: C::Address,
+    pub_key: C::PublicKey,
+    record: TxRecord,
+    events: Vec<TokenEvent<C>>,
+}
+
+impl<C: Context> Indexer<C> {
+    /// Reads the balances credited by the token events a transaction causes if it succeeds,
+    /// before the transaction is dispatched.
+    ///
+    /// The balances must be read in the zk execution as well, so the native execution reads the
+    /// same state and the witness of the slot does not depend on the indexer.
+    pub fn expected_events(
+        &self,
+        events: Vec<TokenEvent<C>>,
+        bank: &Bank<C>,
+        working_set: &mut WorkingSet<C>,
+    ) -> Vec<ExpectedEvent<C>> {
+        events
+            .into_iter()
+            .map(|event| {
+                let (to, coins) = event.credited();
+                let balance_before =
+                    bank.get_balance_of(to.clone(), coins.token_address.clone(), working_set);
+                ExpectedEvent {
+                    event,
+                    balance_before,
+                }
+            })
+            .collect()
+    }
+
+    /// Records the events returned by [`Indexer::expected_events`], for [`Indexer::index_tx`].
+    #[cfg(feature = "native")]
+    pub fn begin_tx(&self, expected: Vec<ExpectedEvent<C>>, working_set: &mut WorkingSet<C>) {
+        self.tx_events
+            .set(&expected, &mut working_set.accessory_state());
+    }
+
+    /// Buffers an executed transaction and the token events it caused.
+    ///
+    /// The events recorded by [`Indexer::begin_tx`] are kept if the balance they credit changed
+    /// by the credited amount, so a reverted transaction causes no event. Its balances were read
+    /// before the transaction, so reading them again does not change the witness of the slot.
+    pub fn index_tx(
+        &self,
+        tx: &Transaction<C>,
+        context: &C,
+        bank: &Bank<C>,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        let expected = self
+            .tx_events
+            .get(&mut working_set.accessory_state())
+            .unwrap_or_default();
+        self.tx_events.delete(&mut working_set.accessory_state());
+        let events = expected
+            .into_iter()
+            .filter(|expected| Self::took_effect(expected, bank, working_set))
+            .map(|expected| expected.event)
+            .collect();
+
+        let serialized_tx = tx
+            .try_to_vec()
+            .expect("Transaction serialization cannot fail");
+        let pending = PendingTx {
+            sender: context.sender().clone(),
+            pub_key: tx.pub_key().clone(),
+            record: TxRecord {
+                hash: <C as Spec>::Hasher::digest(serialized_tx).into(),
+                height: context.slot_height(),
+                nonce: tx.nonce(),
+            },
+            events,
+        };
+
+        let mut accessory_state = working_set.accessory_state();
+        let mut pending_txs = self
+            .pending_txs
+            .get(&mut accessory_state)
+            .unwrap_or_default();
+        pending_txs.push(pending);
+        self.pending_txs.set(&pending_txs, &mut accessory_state);
+    }
+
+    fn took_effect(
+        expected: &ExpectedEvent<C>,
+        bank: &Bank<C>,
+        working_set: &mut WorkingSet<C>,
+    ) -> bool {
+        let (to, coins) = expected.event.credited();
+        if coins.amount == 0 {
+            return false;
+        }
+        let balance_after =
+            bank.get_balance_of(to.clone(), coins.token_address.clone(), working_set);
+        expected
+            .balance_before
+            .unwrap_or_default()
+            .checked_add(coins.amount)
+            == balance_after
+    }
+
+    /// Adds the transactions of the finalized slot to the indexes.
+    pub fn finalize_hook(&self, accessory_working_set: &mut AccessoryWorkingSet<C>) {
+        let Some(pending_txs) = self.pending_txs.get(accessory_working_set) else {
+            return;
+        };
+        self.pending_txs.delete(accessory_working_set);
+
+        for pending in pending_txs {
+            self.add_account(&pending.sender, &pending.pub_key, accessory_working_set);
+
+            let index = self.next_index(
+                &self.sender_tx_count,
+                &pending.sender,
+                accessory_working_set,
+            );
+            self.txs_by_sender.set(
+                &(pending.sender.clone(), index),
+                &pending.record,
+                accessory_working_set,
+            );
+
+            for event in pending.events {
+                match event {
+                    TokenEvent::Transfer { from, to, coins } => {
+                        self.add_holder(&coins.token_address, &to, accessory_working_set);
+                        let transfer = TransferRecord {
+                            from: from.clone(),
+                            to: to.clone(),
+                            coins,
+                            height: pending.record.height,
+                        };
+                        self.add_transfer(&from, &transfer, accessory_working_set);
+                        self.add_transfer(&to, &transfer, accessory_working_set);
+                    }
+                    TokenEvent::Mint { to, coins } => {
+                        self.add_holder(&coins.token_address, &to, accessory_working_set);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_account(
+        &self,
+        sender: &C::Address,
+        pub_key: &C::PublicKey,
+        accessory_working_set: &mut AccessoryWorkingSet<C>,
+    ) {
+        if self
+            .is_indexed_account
+            .get(sender, accessory_working_set)
+            .unwrap_or_default()
+        {
+            return;
+        }
+        self.is_indexed_account
+            .set(sender, &true, accessory_working_set);
+
+        let index = self
+            .account_count
+            .get(accessory_working_set)
+            .unwrap_or_default();
+        self.account_count.set(&(index + 1), accessory_working_set);
+        self.accounts.set(&index, pub_key, accessory_working_set);
+    }
+
+    fn add_holder(
+        &self,
+        token_address: &C::Address,
+        holder: &C::Address,
+        accessory_working_set: &mut AccessoryWorkingSet<C>,
+    ) {
+        let key = (token_address.clone(), holder.clone());
+        if self
+            .is_token_holder
+            .get(&key, accessory_working_set)
+            .unwrap_or_default()
+        {
+            return;
+        }
+        self.is_token_holder.set(&key, &true, accessory_working_set);
+
+        let index = self.next_index(
+            &self.token_holder_count,
+            token_address,
+            accessory_working_set,
+        );
+        if index == 0 {
+            let token_index = self
+                .token_count
+                .get(accessory_working_set)
+                .unwrap_or_default();
+            self.token_count
+                .set(&(token_index + 1), accessory_working_set);
+            self.tokens
+                .set(&token_index, token_address, accessory_working_set);
+        }
+        self.token_holders.set(
+            &(token_address.clone(), index),
+            holder,
+            accessory_working_set,
+        );
+    }
+
+    fn add_transfer(
+        &self,
+        address: &C::Address,
+        transfer: &TransferRecord<C>,
+        accessory_working_set: &mut AccessoryWorkingSet<C>,
+    ) {
+        let index = self.next_index(&self.transfer_count, address, accessory_working_set);
+        self.transfers_by_address
+            .set(&(address.clone(), index), transfer, accessory_working_set);
+    }
+
+    /// Increments the counter of `key`, returning the position of the new entry.
+    fn next_index(
+        &self,
+        counter: &sov_modules_api::AccessoryStateMap<C::Address, u64>,
+        key: &C::Address,
+        accessory_working_set: &mut AccessoryWorkingSet<C>,
+    ) -> u64 {
+        let index = counter.get(key, accessory_working_set).unwrap_or_default();
+        counter.set(key, &(index + 1), accessory_working_set);
+        index
+    }
+}
@@ -0,0 +1,160 @@
+#![deny(missing_docs)]
+//! The `indexer` module maintains explorer indexes in accessory state: the transactions of each
+//! sender, the holders of each token and the transfers of each address. It also lists the
+//! accounts that sent transactions and the tokens that were held, which state maps cannot
+//! enumerate.
+//!
+//! Accessory state is only written by native nodes and is not part of the state root, so the
+//! indexes do not affect the proofs. Transactions are buffered while a slot executes, and the
+//! indexes are updated once the slot is finalized. Token events are derived from the call
+//! messages of the transactions, and only recorded if the balance they credit changed
+//! accordingly, so reverted transactions cause no token events. Transfers to the sender change
+//! no balance, and are not recorded.
+
+mod hooks;
+#[cfg(feature = "native")]
+mod query;
+
+pub use hooks::*;
+#[cfg(feature = "native")]
+pub use query::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_bank::Coins;
+use sov_modules_api::{
+    AccessoryStateMap, AccessoryStateValue, CallResponse, Context, Error, ModuleInfo, WorkingSet,
+};
+
+/// A transaction sent by an indexed address.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct TxRecord {
+    /// The hash of the transaction.
+    pub hash: [u8; 32],
+    /// The rollup height the transaction was executed at.
+    pub height: u64,
+    /// The nonce of the transaction.
+    pub nonce: u64,
+}
+
+/// A token transfer from or to an indexed address.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(bound = "C::Address: Serialize + DeserializeOwned")]
+pub struct TransferRecord<C: Context> {
+    /// The sender of the tokens.
+    pub from: C::Address,
+    /// The recipient of the tokens.
+    pub to: C::Address,
+    /// The transferred tokens.
+    pub coins: Coins<C>,
+    /// The rollup height the transfer was executed at.
+    pub height: u64,
+}
+
+/// The indexer module.
+#[derive(Clone, ModuleInfo)]
+pub struct Indexer<C: Context> {
+    /// The address of the module.
+    #[address]
+    pub(crate) address: C::Address,
+
+    /// The transactions of the slot being executed, indexed when the slot is finalized.
+    #[state]
+    pub(crate) pending_txs: AccessoryStateValue<Vec<PendingTx<C>>>,
+
+    /// The token events of the transaction being executed, if it succeeds.
+    #[state]
+    pub(crate) tx_events: AccessoryStateValue<Vec<ExpectedEvent<C>>>,
+
+    /// The number of transactions sent by each address.
+    #[state]
+    pub(crate) sender_tx_count: AccessoryStateMap<C::Address, u64>,
+
+    /// The transactions of each sender, keyed by sender and position.
+    #[state]
+    pub(crate) txs_by_sender: AccessoryStateMap<(C::Address, u64), TxRecord>,
+
+    /// The number of known holders of each token.
+    #[state]
+    pub(crate) token_holder_count: AccessoryStateMap<C::Address, u64>,
+
+    /// The holders of each token, keyed by token and position.
+    #[state]
+    pub(crate) token_holders: AccessoryStateMap<(C::Address, u64), C::Address>,
+
+    /// Whether an address is already listed as a holder of a token, keyed by token and holder.
+    #[state]
+    pub(crate) is_token_holder: AccessoryStateMap<(C::Address, C::Address), bool>,
+
+    /// The number of accounts that sent transactions.
+    #[state]
+    pub(crate) account_count: AccessoryStateValue<u64>,
+
+    /// The public keys of the accounts that sent transactions, keyed by position.
+    #[state]
+    pub(crate) accounts: AccessoryStateMap<u64, C::PublicKey>,
+
+    /// Whether an address is already listed in `accounts`.
+    #[state]
+    pub(crate) is_indexed_account: AccessoryStateMap<C::Address, bool>,
+
+    /// The number of tokens that were held.
+    #[state]
+    pub(crate) token_count: AccessoryStateValue<u64>,
+
+    /// The tokens that were held, keyed by position.
+    #[state]
+    pub(crate) tokens: AccessoryStateMap<u64, C::Address>,
+
+    /// The number of transfers from or to each address.
+    #[state]
+    pub(crate) transfer_count: AccessoryStateMap<C::Address, u64>,
+
+    /// The transfers from or to each address, keyed by address and position.
+    #[state]
+    pub(crate) transfers_by_address: AccessoryStateMap<(C::Address, u64), TransferRecord<C>>,
+}
+
+impl<C: Context> sov_modules_api::Module for Indexer<C> {
+    type Context = C;
+
+    type Config = ();
+
+    type CallMessage = ();
+
+    type Event = ();
+
+    fn genesis(
+        &self,
+        _config: &Self::Config,
+        _working_set: &mut WorkingSet<C>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        _msg: Self::CallMessage,
+        _context: &Self::Context,
+        _working_set: &mut WorkingSet<C>,
+    ) -> Result<CallResponse, Error> {
+        Ok(CallResponse::default())
+    }
+}
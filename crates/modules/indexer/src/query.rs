@@ -0,0 +1,148 @@
+//! Defines rpc queries exposed by the indexer module, along with the relevant types
+use jsonrpsee::core::RpcResult;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::macros::rpc_gen;
+use sov_modules_api::{AccessoryStateMap, AccessoryStateValue, Context, WorkingSet};
+
+use crate::{Indexer, TransferRecord, TxRecord};
+
+/// The maximum number of entries returned by a single call.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// A page of an index, the response type of all the `indexer_*` RPC methods.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+pub struct Page<T> {
+    /// The entries at positions `offset..offset + limit`, in the order they were indexed.
+    pub items: Vec<T>,
+    /// The total number of entries in the index.
+    pub total: u64,
+}
+
+#[rpc_gen(client, server, namespace = "indexer")]
+impl<C: Context> Indexer<C> {
+    /// Returns the transactions sent by `sender`.
+    #[rpc_method(name = "getTransactionsBySender")]
+    pub fn transactions_by_sender(
+        &self,
+        sender: C::Address,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<Page<TxRecord>> {
+        Ok(self.page(
+            &self.sender_tx_count,
+            &self.txs_by_sender,
+            sender,
+            offset,
+            limit,
+            working_set,
+        ))
+    }
+
+    /// Returns the addresses that have held `token_address`.
+    #[rpc_method(name = "getTokenHolders")]
+    pub fn token_holders_of(
+        &self,
+        token_address: C::Address,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<Page<C::Address>> {
+        Ok(self.page(
+            &self.token_holder_count,
+            &self.token_holders,
+            token_address,
+            offset,
+            limit,
+            working_set,
+        ))
+    }
+
+    /// Returns the public keys of the accounts that sent transactions.
+    #[rpc_method(name = "getAccounts")]
+    pub fn accounts(
+        &self,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<Page<C::PublicKey>> {
+        Ok(self.list_page(
+            &self.account_count,
+            &self.accounts,
+            offset,
+            limit,
+            working_set,
+        ))
+    }
+
+    /// Returns the tokens that have been held.
+    #[rpc_method(name = "getTokens")]
+    pub fn tokens(
+        &self,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<Page<C::Address>> {
+        Ok(self.list_page(&self.token_count, &self.tokens, offset, limit, working_set))
+    }
+
+    /// Returns the transfers from or to `address`.
+    #[rpc_method(name = "getTransfers")]
+    pub fn transfers_of(
+        &self,
+        address: C::Address,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<Page<TransferRecord<C>>> {
+        Ok(self.page(
+            &self.transfer_count,
+            &self.transfers_by_address,
+            address,
+            offset,
+            limit,
+            working_set,
+        ))
+    }
+}
+
+impl<C: Context> Indexer<C> {
+    fn page<T: borsh::BorshSerialize + borsh::BorshDeserialize>(
+        &self,
+        count: &AccessoryStateMap<C::Address, u64>,
+        index: &AccessoryStateMap<(C::Address, u64), T>,
+        key: C::Address,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> Page<T> {
+        let mut accessory_state = working_set.accessory_state();
+        let total = count.get(&key, &mut accessory_state).unwrap_or_default();
+        let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total);
+
+        let items = (offset..end)
+            .filter_map(|position| index.get(&(key.clone(), position), &mut accessory_state))
+            .collect();
+        Page { items, total }
+    }
+
+    fn list_page<T: borsh::BorshSerialize + borsh::BorshDeserialize>(
+        &self,
+        count: &AccessoryStateValue<u64>,
+        index: &AccessoryStateMap<u64, T>,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> Page<T> {
+        let mut accessory_state = working_set.accessory_state();
+        let total = count.get(&mut accessory_state).unwrap_or_default();
+        let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total);
+
+        let items = (offset..end)
+            .filter_map(|position| index.get(&position, &mut accessory_state))
+            .collect();
+        Page { items, total }
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{Context, DaSpec, WorkingSet};
+
+use crate::SequencerRewards;
+
+/// Config for the [`SequencerRewards`] module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "C::Address: Serialize + DeserializeOwned")]
+pub struct SequencerRewardsConfig<C: Context, Da: DaSpec> {
+    /// The token rewards are paid in. Must match `GAS_TOKEN_ADDRESS` from `constants.json`.
+    pub reward_token_address: C::Address,
+    /// The account rewards are paid from. It must be funded with the reward token at genesis.
+    pub reward_pool_address: C::Address,
+    /// The DA and payout addresses of the sequencers registered at genesis. It is not part of the
+    /// genesis files: it is filled from the `sequencer_registry` genesis when they are read.
+    #[serde(skip)]
+    pub payout_addresses: Vec<(Da::Address, C::Address)>,
+}
+
+impl<C: Context, Da: DaSpec> SequencerRewards<C, Da> {
+    pub(crate) fn init_module(
+        &self,
+        config: &<Self as sov_modules_api::Module>::Config,
+        working_set: &mut WorkingSet<C>,
+    ) -> Result<()> {
+        self.reward_token
+            .set(&config.reward_token_address, working_set);
+        self.reward_pool
+            .set(&config.reward_pool_address, working_set);
+        for (sequencer_da_address, payout_address) in &config.payout_addresses {
+            self.payout_addresses
+                .set(sequencer_da_address, payout_address, working_set);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use sov_bank::Coins;
+use sov_modules_api::{Context, DaSpec, WorkingSet};
+use tracing::{info, warn};
+
+use crate::{Registration, SequencerRewards, SlashReason, SlashRecord, MAX_RECORDED_SLASHES};
+
+impl<C: Context, Da: DaSpec> SequencerRewards<C, Da> {
+    /// Remembers the sender of the batch about to be executed, so that it can be rewarded
+    /// once the batch is processed.
+    pub fn begin_blob_hook(
+        &self,
+        sequencer_da_address: &Da::Address,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        self.current_sequencer
+            .set(sequencer_da_address, working_set);
+    }
+
+    /// Pays `reward` to the sequencer of the batch that was just executed.
+    ///
+    /// Failing to pay the reward must not halt the rollup, so the rewards that cannot be paid are
+    /// added to the unpaid rewards of the sequencer rather than returned as errors: the
+    /// cumulative counter only reflects rewards that were actually paid.
+    pub fn reward_sequencer(&self, reward: u64, working_set: &mut WorkingSet<C>) {
+        let Some(sequencer) = self.current_sequencer.remove(working_set) else {
+            warn!("Sequencer rewarded outside of a batch, ignoring the reward");
+            return;
+        };
+
+        if reward == 0 {
+            return;
+        }
+
+        let Some(payout_address) = self.get_payout_address(&sequencer, working_set) else {
+            warn!(
+                "Sequencer {} is not registered, reward of {} not paid",
+                sequencer, reward
+            );
+            self.record_unpaid_reward(&sequencer, reward, working_set);
+            return;
+        };
+
+        let token_address = self
+            .reward_token
+            .get(working_set)
+            .expect("Reward token must be set at genesis");
+        let reward_pool = self
+            .reward_pool
+            .get(working_set)
+            .expect("Reward pool must be set at genesis");
+
+        let coins = Coins {
+            amount: reward,
+            token_address,
+        };
+        if let Err(e) = self
+            .bank
+            .transfer_from(&reward_pool, &payout_address, coins, working_set)
+        {
+            warn!(
+                "Unable to pay reward of {} to sequencer {}: {}",
+                reward, sequencer, e
+            );
+            self.record_unpaid_reward(&sequencer, reward, working_set);
+            return;
+        }
+
+        let total = self
+            .cumulative_rewards
+            .get(&sequencer, working_set)
+            .unwrap_or_default()
+            .saturating_add(reward);
+        self.cumulative_rewards.set(&sequencer, &total, working_set);
+
+        info!(
+            "Sequencer {} rewarded with {}, total paid: {}",
+            sequencer, reward, total
+        );
+    }
+
+    fn record_unpaid_reward(
+        &self,
+        sequencer: &Da::Address,
+        reward: u64,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        let unpaid = self
+            .get_unpaid_rewards(sequencer, working_set)
+            .saturating_add(reward);
+        self.unpaid_rewards.set(sequencer, &unpaid, working_set);
+    }
+
+    /// Forgets the sender of a batch that did not earn a reward.
+    pub fn end_blob_hook(&self, working_set: &mut WorkingSet<C>) {
+        self.current_sequencer.delete(working_set);
+    }
+
+    /// Records that the sender of the batch that was just executed was slashed, forfeiting
+    /// the coins it locked in the sequencer registry. The record replaces the oldest one once
+    /// [`MAX_RECORDED_SLASHES`] are kept.
+    pub fn slash_sequencer(
+        &self,
+        sequencer_da_address: &Da::Address,
+        reason: SlashReason,
+        height: u64,
+        forfeited: Option<Coins<C>>,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        self.current_sequencer.delete(working_set);
+        // The sequencer registry forgets the slashed sequencer
+        self.payout_addresses
+            .delete(sequencer_da_address, working_set);
+
+        let position = self.get_slash_count(working_set);
+        let record = SlashRecord {
+            sequencer_da_address: sequencer_da_address.clone(),
+            reason,
+            height,
+            forfeited,
+        };
+        self.slashes
+            .set(&(position % MAX_RECORDED_SLASHES), &record, working_set);
+        self.slash_count.set(&(position + 1), working_set);
+    }
+
+    /// Remembers a registration or exit sent to the sequencer registry by `sender`, before the
+    /// transaction is executed.
+    pub fn begin_registration(
+        &self,
+        sequencer_da_address: Da::Address,
+        sender: C::Address,
+        exit: bool,
+        locked: Coins<C>,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        let balance_before = self
+            .bank
+            .get_balance_of(sender.clone(), locked.token_address.clone(), working_set)
+            .unwrap_or_default();
+        let registration = Registration {
+            sequencer_da_address,
+            sender,
+            exit,
+            locked,
+            balance_before,
+        };
+        self.pending_registration.set(&registration, working_set);
+    }
+
+    /// Updates the payout addresses once the transaction remembered by
+    /// [`Self::begin_registration`] is executed.
+    ///
+    /// The call took effect if the sequencer registry locked the coins of a registration, or
+    /// returned the coins of an exit. The registered sequencer is then paid to the sender of the
+    /// registration, and the sequencer that exited is no longer paid. Without coins to lock, the
+    /// outcome of the call cannot be told apart, and the payout addresses are left unchanged.
+    pub fn end_registration(&self, working_set: &mut WorkingSet<C>) {
+        let Some(registration) = self.pending_registration.remove(working_set) else {
+            return;
+        };
+
+        let balance = self
+            .bank
+            .get_balance_of(
+                registration.sender.clone(),
+                registration.locked.token_address.clone(),
+                working_set,
+            )
+            .unwrap_or_default();
+        let amount = registration.locked.amount;
+        if amount == 0 {
+            warn!(
+                "The sequencer registry locks no coins, the payout address of {} is not updated",
+                registration.sequencer_da_address
+            );
+        } else if registration.exit {
+            if registration.balance_before.checked_add(amount) == Some(balance) {
+                self.payout_addresses
+                    .delete(&registration.sequencer_da_address, working_set);
+            }
+        } else if registration.balance_before.checked_sub(amount) == Some(balance) {
+            info!(
+                "Sequencer {} registered, paying its rewards to {}",
+                registration.sequencer_da_address, registration.sender
+            );
+            self.payout_addresses.set(
+                &registration.sequencer_da_address,
+                &registration.sender,
+                working_set,
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+#![deny(missing_docs)]
+//! The `sequencer-rewards` module pays out the rewards earned by sequencers for the batches they
+//! submit, and keeps track of how much each sequencer has been paid so far.
+//!
+//! Rewards are paid in the rollup gas token (`GAS_TOKEN_ADDRESS` in `constants.json`) out of a
+//! reward pool account configured at genesis, to the rollup address the sequencer registered with
+//! in the sequencer registry. The module keeps its own copy of these payout addresses: the ones of
+//! the genesis sequencers, and the ones of the registrations and exits the runtime reports once
+//! they took effect. The rewards that cannot be paid, because the sequencer is not registered or
+//! the reward pool is short, are recorded as unpaid. The module also keeps the last
+//! [`MAX_RECORDED_SLASHES`] sequencers slashed for the batches they submitted.
+
+mod genesis;
+mod hooks;
+#[cfg(feature = "native")]
+mod query;
+
+pub use genesis::*;
+#[cfg(feature = "native")]
+pub use query::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_bank::Coins;
+use sov_modules_api::{
+    CallResponse, Context, DaSpec, Error, ModuleInfo, StateMap, StateValue, WorkingSet,
+};
+
+/// The number of slashing events kept in state: each new event replaces the oldest one.
+pub const MAX_RECORDED_SLASHES: u64 = 1000;
+
+/// Why the batch of a slashed sequencer was rejected.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum SlashReason {
+    /// The batch could not be deserialized.
+    InvalidBatchEncoding,
+    /// A transaction of the batch failed its stateless verification.
+    StatelessVerificationFailed,
+    /// A transaction of the batch could not be deserialized.
+    InvalidTransactionEncoding,
+}
+
+/// A sequencer slashed for a batch it submitted.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(
+    bound = "C::Address: Serialize + DeserializeOwned, Da::Address: Serialize + DeserializeOwned"
+)]
+pub struct SlashRecord<C: Context, Da: DaSpec> {
+    /// The DA address of the slashed sequencer.
+    pub sequencer_da_address: Da::Address,
+    /// Why the batch was rejected.
+    pub reason: SlashReason,
+    /// The rollup height the batch was processed at.
+    pub height: u64,
+    /// The coins the sequencer locked in the sequencer registry, which it forfeits, if the
+    /// registry locks any.
+    pub forfeited: Option<Coins<C>>,
+}
+
+/// A registration or exit sent to the sequencer registry, checked once it is executed.
+#[derive(Debug, Clone, PartialEq, Eq, borsh::BorshDeserialize, borsh::BorshSerialize)]
+pub struct Registration<C: Context, Da: DaSpec> {
+    /// The DA address of the sequencer.
+    pub sequencer_da_address: Da::Address,
+    /// The rollup address that sent the call, which the rewards are paid to.
+    pub sender: C::Address,
+    /// Whether the sequencer exits rather than registers.
+    pub exit: bool,
+    /// The coins the sequencer registry locks for a registration.
+    pub locked: Coins<C>,
+    /// The balance of `sender` in the locked token before the call.
+    pub balance_before: u64,
+}
+
+/// The sequencer rewards module.
+#[derive(Clone, ModuleInfo)]
+pub struct SequencerRewards<C: Context, Da: DaSpec> {
+    /// The address of the module.
+    #[address]
+    pub(crate) address: C::Address,
+
+    /// The token in which rewards are paid.
+    #[state]
+    pub(crate) reward_token: StateValue<C::Address>,
+
+    /// The account the rewards are paid from.
+    #[state]
+    pub(crate) reward_pool: StateValue<C::Address>,
+
+    /// The DA address of the sequencer whose batch is currently being executed.
+    #[state]
+    pub(crate) current_sequencer: StateValue<Da::Address>,
+
+    /// The total amount of rewards paid to each sequencer, keyed by its DA address.
+    #[state]
+    pub(crate) cumulative_rewards: StateMap<Da::Address, u64>,
+
+    /// The total amount of rewards earned by each sequencer but not paid, keyed by its DA
+    /// address.
+    #[state]
+    pub(crate) unpaid_rewards: StateMap<Da::Address, u64>,
+
+    /// The rollup address the rewards of each registered sequencer are paid to, keyed by its DA
+    /// address.
+    #[state]
+    pub(crate) payout_addresses: StateMap<Da::Address, C::Address>,
+
+    /// The registration of the transaction being executed, if it calls the sequencer registry.
+    #[state]
+    pub(crate) pending_registration: StateValue<Registration<C, Da>>,
+
+    /// The number of slashing events recorded so far.
+    #[state]
+    pub(crate) slash_count: StateValue<u64>,
+
+    /// The last [`MAX_RECORDED_SLASHES`] slashing events, keyed by their position modulo
+    /// [`MAX_RECORDED_SLASHES`].
+    #[state]
+    pub(crate) slashes: StateMap<u64, SlashRecord<C, Da>>,
+
+    /// Reference to the bank module, used to transfer the rewards.
+    #[module]
+    pub(crate) bank: sov_bank::Bank<C>,
+}
+
+impl<C: Context, Da: DaSpec> sov_modules_api::Module for SequencerRewards<C, Da> {
+    type Context = C;
+
+    type Config = SequencerRewardsConfig<C, Da>;
+
+    type CallMessage = ();
+
+    type Event = ();
+
+    fn genesis(&self, config: &Self::Config, working_set: &mut WorkingSet<C>) -> Result<(), Error> {
+        Ok(self.init_module(config, working_set)?)
+    }
+
+    fn call(
+        &self,
+        _msg: Self::CallMessage,
+        _context: &Self::Context,
+        _working_set: &mut WorkingSet<C>,
+    ) -> Result<CallResponse, Error> {
+        Ok(CallResponse::default())
+    }
+}
+
+impl<C: Context, Da: DaSpec> SequencerRewards<C, Da> {
+    /// Returns the total amount of rewards paid to the sequencer with the given DA address.
+    pub fn get_cumulative_rewards(
+        &self,
+        sequencer_da_address: &Da::Address,
+        working_set: &mut WorkingSet<C>,
+    ) -> u64 {
+        self.cumulative_rewards
+            .get(sequencer_da_address, working_set)
+            .unwrap_or_default()
+    }
+
+    /// Returns the total amount of rewards earned by the sequencer with the given DA address that
+    /// could not be paid.
+    pub fn get_unpaid_rewards(
+        &self,
+        sequencer_da_address: &Da::Address,
+        working_set: &mut WorkingSet<C>,
+    ) -> u64 {
+        self.unpaid_rewards
+            .get(sequencer_da_address, working_set)
+            .unwrap_or_default()
+    }
+
+    /// Returns the rollup address that receives the rewards of the given sequencer: the address
+    /// it registered with in the sequencer registry, if it is registered.
+    pub fn get_payout_address(
+        &self,
+        sequencer_da_address: &Da::Address,
+        working_set: &mut WorkingSet<C>,
+    ) -> Option<C::Address> {
+        self.payout_addresses.get(sequencer_da_address, working_set)
+    }
+
+    /// Returns the number of slashing events recorded so far.
+    pub fn get_slash_count(&self, working_set: &mut WorkingSet<C>) -> u64 {
+        self.slash_count.get(working_set).unwrap_or_default()
+    }
+
+    /// Returns the slashing event at the given position, starting from 0, if it is one of the
+    /// last [`MAX_RECORDED_SLASHES`] events.
+    pub fn get_slash(
+        &self,
+        position: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> Option<SlashRecord<C, Da>> {
+        let count = self.get_slash_count(working_set);
+        if position >= count || position < count.saturating_sub(MAX_RECORDED_SLASHES) {
+            return None;
+        }
+        self.slashes
+            .get(&(position % MAX_RECORDED_SLASHES), working_set)
+    }
+
+    /// Returns the address of the token rewards are paid in.
+    pub fn get_reward_token(&self, working_set: &mut WorkingSet<C>) -> Option<C::Address> {
+        self.reward_token.get(working_set)
+    }
+}
@@ -0,0 +1,87 @@
+//! Defines rpc queries exposed by the sequencer rewards module, along with the relevant types
+use jsonrpsee::core::RpcResult;
+use sov_modules_api::macros::rpc_gen;
+use sov_modules_api::{Context, DaSpec, WorkingSet};
+
+use crate::{SequencerRewards, SlashRecord};
+
+/// The maximum number of slashing events returned by `sequencerRewards_getSlashes`.
+pub const MAX_SLASHES_PER_PAGE: u64 = 100;
+
+/// The response type to the `sequencerRewards_getRewards` RPC method.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RewardsResponse<C: Context> {
+    /// The rollup address the rewards are paid to, if the sequencer is registered.
+    pub payout_address: Option<C::Address>,
+    /// The total amount of rewards paid to the sequencer so far.
+    pub amount: u64,
+    /// The total amount of rewards earned by the sequencer but not paid, because it was not
+    /// registered or the reward pool could not pay them.
+    pub unpaid: u64,
+}
+
+/// The response type to the `sequencerRewards_getRewardToken` RPC method.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RewardTokenResponse<C: Context> {
+    /// The address of the token rewards are paid in.
+    pub token_address: Option<C::Address>,
+}
+
+/// The response type to the `sequencerRewards_getSlashes` RPC method.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(
+    bound = "C::Address: serde::Serialize + serde::de::DeserializeOwned, Da::Address: serde::Serialize + serde::de::DeserializeOwned"
+)]
+pub struct SlashesResponse<C: Context, Da: DaSpec> {
+    /// The slashing events at positions `offset..offset + limit`, oldest first. Only the last
+    /// [`crate::MAX_RECORDED_SLASHES`] events are kept, the older positions are skipped.
+    pub slashes: Vec<SlashRecord<C, Da>>,
+    /// The total number of slashing events, including the ones no longer kept.
+    pub total: u64,
+}
+
+#[rpc_gen(client, server, namespace = "sequencerRewards")]
+impl<C: Context, Da: DaSpec> SequencerRewards<C, Da> {
+    /// Returns the rewards paid so far to the sequencer with the given DA address.
+    #[rpc_method(name = "getRewards")]
+    pub fn rewards_of(
+        &self,
+        sequencer_da_address: Da::Address,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<RewardsResponse<C>> {
+        Ok(RewardsResponse {
+            payout_address: self.get_payout_address(&sequencer_da_address, working_set),
+            amount: self.get_cumulative_rewards(&sequencer_da_address, working_set),
+            unpaid: self.get_unpaid_rewards(&sequencer_da_address, working_set),
+        })
+    }
+
+    /// Returns the slashing events, oldest first.
+    #[rpc_method(name = "getSlashes")]
+    pub fn slashes(
+        &self,
+        offset: u64,
+        limit: u64,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<SlashesResponse<C, Da>> {
+        let total = self.get_slash_count(working_set);
+        let end = offset
+            .saturating_add(limit.min(MAX_SLASHES_PER_PAGE))
+            .min(total);
+        let slashes = (offset..end)
+            .filter_map(|position| self.get_slash(position, working_set))
+            .collect();
+        Ok(SlashesResponse { slashes, total })
+    }
+
+    /// Returns the address of the token rewards are paid in.
+    #[rpc_method(name = "getRewardToken")]
+    pub fn reward_token(
+        &self,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<RewardTokenResponse<C>> {
+        Ok(RewardTokenResponse {
+            token_address: self.get_reward_token(working_set),
+        })
+    }
+}
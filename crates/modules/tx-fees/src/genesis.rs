@@ -0,0 +1,31 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{Context, WorkingSet};
+
+use crate::TxFees;
+
+/// Config for the [`TxFees`] module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "C::Address: Serialize + DeserializeOwned")]
+pub struct TxFeesConfig<C: Context> {
+    /// The token fees are paid in. Must match `GAS_TOKEN_ADDRESS` from `constants.json`.
+    pub gas_token_address: C::Address,
+    /// The price of a unit of gas, in gas tokens. A price of zero disables gas metering.
+    pub gas_price: u64,
+    /// The account consumed gas is paid to.
+    pub fee_recipient: C::Address,
+}
+
+impl<C: Context> TxFees<C> {
+    pub(crate) fn init_module(
+        &self,
+        config: &<Self as sov_modules_api::Module>::Config,
+        working_set: &mut WorkingSet<C>,
+    ) -> Result<()> {
+        self.gas_token.set(&config.gas_token_address, working_set);
+        self.gas_price.set(&config.gas_price, working_set);
+        self.fee_recipient.set(&config.fee_recipient, working_set);
+        Ok(())
+    }
+}
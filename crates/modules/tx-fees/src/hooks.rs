@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Context as _, Result};
+use sov_bank::Coins;
+use sov_modules_api::transaction::Transaction;
+use sov_modules_api::{Context, GasUnit, WorkingSet};
+
+use crate::TxFees;
+
+impl<C: Context> TxFees<C> {
+    /// Reserves the maximum fee of `tx` from its sender and starts metering its gas.
+    ///
+    /// Fails if the sender cannot cover `gas_limit * gas_price + gas_tip`.
+    pub fn reserve_gas(
+        &self,
+        tx: &Transaction<C>,
+        sender: &C::Address,
+        working_set: &mut WorkingSet<C>,
+    ) -> Result<()> {
+        let gas_price = self.get_gas_price(working_set);
+        let max_fee = max_gas_fee(tx, gas_price)?
+            .checked_add(tx.gas_tip())
+            .ok_or_else(|| anyhow!("Transaction fee overflows"))?;
+
+        if max_fee > 0 {
+            let coins = Coins {
+                amount: max_fee,
+                token_address: self.gas_token(working_set),
+            };
+            self.bank
+                .transfer_from(sender, &self.address, coins, working_set)
+                .with_context(|| {
+                    format!(
+                        "Sender {} cannot cover the transaction fee of {}",
+                        sender, max_fee
+                    )
+                })?;
+        }
+
+        if gas_price > 0 {
+            let price = C::GasUnit::from_arbitrary_dimensions(&[gas_price, gas_price]);
+            working_set.set_gas(max_gas_fee(tx, gas_price)?, price);
+        }
+
+        Ok(())
+    }
+
+    /// Settles the fee of `tx` once it has been executed: the unused gas is refunded to the
+    /// sender, the consumed gas is paid to the fee recipient and the tip to the sequencer.
+    pub fn settle_gas(
+        &self,
+        tx: &Transaction<C>,
+        context: &C,
+        working_set: &mut WorkingSet<C>,
+    ) -> Result<()> {
+        let gas_price = self.get_gas_price(working_set);
+        let reserved = max_gas_fee(tx, gas_price)?;
+        let refund = if gas_price > 0 {
+            working_set.gas_remaining_funds().min(reserved)
+        } else {
+            0
+        };
+        let consumed = reserved - refund;
+
+        let fee_recipient = self
+            .fee_recipient
+            .get(working_set)
+            .expect("Fee recipient must be set at genesis");
+
+        self.pay(context.sender(), refund, working_set)?;
+        self.pay(&fee_recipient, consumed, working_set)?;
+        self.pay(context.sequencer(), tx.gas_tip(), working_set)
+    }
+
+    fn pay(&self, to: &C::Address, amount: u64, working_set: &mut WorkingSet<C>) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let coins = Coins {
+            amount,
+            token_address: self.gas_token(working_set),
+        };
+        self.bank
+            .transfer_from(&self.address, to, coins, working_set)?;
+        Ok(())
+    }
+
+    fn gas_token(&self, working_set: &mut WorkingSet<C>) -> C::Address {
+        self.gas_token
+            .get(working_set)
+            .expect("Gas token must be set at genesis")
+    }
+}
+
+/// The part of the fee of `tx` that pays for gas, before any refund.
+fn max_gas_fee<C: Context>(tx: &Transaction<C>, gas_price: u64) -> Result<u64> {
+    tx.gas_limit()
+        .checked_mul(gas_price)
+        .ok_or_else(|| anyhow!("Transaction gas limit overflows"))
+}
@@ -0,0 +1,78 @@
+#![deny(missing_docs)]
+//! The `tx-fees` module charges transaction fees in the rollup gas token.
+//!
+//! Before a transaction is dispatched, `gas_limit * gas_price + gas_tip` is reserved from the
+//! sender. Once the transaction is executed, the unused gas is refunded to the sender, the
+//! consumed gas is paid to the fee recipient and the tip is paid to the sequencer.
+//!
+//! The gas consumed by each call is metered by the modules themselves, according to the gas
+//! schedule in `constants.json`.
+
+mod genesis;
+mod hooks;
+#[cfg(feature = "native")]
+mod query;
+
+pub use genesis::*;
+#[cfg(feature = "native")]
+pub use query::*;
+use sov_modules_api::{CallResponse, Context, Error, ModuleInfo, StateValue, WorkingSet};
+
+/// The transaction fees module.
+#[derive(Clone, ModuleInfo)]
+pub struct TxFees<C: Context> {
+    /// The address of the module. Reserved fees are held here while a transaction executes.
+    #[address]
+    pub(crate) address: C::Address,
+
+    /// The token fees are paid in.
+    #[state]
+    pub(crate) gas_token: StateValue<C::Address>,
+
+    /// The price of a unit of gas, in gas tokens.
+    #[state]
+    pub(crate) gas_price: StateValue<u64>,
+
+    /// The account consumed gas is paid to.
+    #[state]
+    pub(crate) fee_recipient: StateValue<C::Address>,
+
+    /// Reference to the bank module, used to move the fees around.
+    #[module]
+    pub(crate) bank: sov_bank::Bank<C>,
+}
+
+impl<C: Context> sov_modules_api::Module for TxFees<C> {
+    type Context = C;
+
+    type Config = TxFeesConfig<C>;
+
+    type CallMessage = ();
+
+    type Event = ();
+
+    fn genesis(&self, config: &Self::Config, working_set: &mut WorkingSet<C>) -> Result<(), Error> {
+        Ok(self.init_module(config, working_set)?)
+    }
+
+    fn call(
+        &self,
+        _msg: Self::CallMessage,
+        _context: &Self::Context,
+        _working_set: &mut WorkingSet<C>,
+    ) -> Result<CallResponse, Error> {
+        Ok(CallResponse::default())
+    }
+}
+
+impl<C: Context> TxFees<C> {
+    /// Returns the price of a unit of gas, in gas tokens.
+    pub fn get_gas_price(&self, working_set: &mut WorkingSet<C>) -> u64 {
+        self.gas_price.get(working_set).unwrap_or_default()
+    }
+
+    /// Returns the address of the token fees are paid in.
+    pub fn get_gas_token(&self, working_set: &mut WorkingSet<C>) -> Option<C::Address> {
+        self.gas_token.get(working_set)
+    }
+}
@@ -0,0 +1,30 @@
+//! Defines rpc queries exposed by the tx fees module, along with the relevant types
+use jsonrpsee::core::RpcResult;
+use sov_modules_api::macros::rpc_gen;
+use sov_modules_api::{Context, WorkingSet};
+
+use crate::TxFees;
+
+/// The response type to the `txFees_getGasPrice` RPC method.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct GasPriceResponse<C: Context> {
+    /// The token fees are paid in.
+    pub token_address: Option<C::Address>,
+    /// The price of a unit of gas, in gas tokens.
+    pub gas_price: u64,
+}
+
+#[rpc_gen(client, server, namespace = "txFees")]
+impl<C: Context> TxFees<C> {
+    /// Returns the current gas price, so that wallets can pick a gas limit the sender can cover.
+    #[rpc_method(name = "getGasPrice")]
+    pub fn current_gas_price(
+        &self,
+        working_set: &mut WorkingSet<C>,
+    ) -> RpcResult<GasPriceResponse<C>> {
+        Ok(GasPriceResponse {
+            token_address: self.get_gas_token(working_set),
+            gas_price: self.get_gas_price(working_set),
+        })
+    }
+}
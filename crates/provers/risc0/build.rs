@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
 fn main() {
+    // The celestia guest embeds the namespaces from `constants.json`
+    println!("cargo:rerun-if-changed=../../../constants.json");
+
     if std::env::var("SKIP_GUEST_BUILD").is_ok() {
         println!("Skipping guest build for CI run");
         let out_dir = std::env::var_os("OUT_DIR").unwrap();
@@ -10,6 +13,8 @@ fn main() {
         let elf = r#"
             pub const ROLLUP_ELF: &[u8] = &[];
             pub const MOCK_DA_ELF: &[u8] = &[];
+            pub const ROLLUP_ID: [u32; 8] = [0; 8];
+            pub const MOCK_DA_ID: [u32; 8] = [0; 8];
         "#;
 
         std::fs::write(methods_path, elf).expect("Failed to write mock rollup elf");
@@ -10,14 +10,24 @@ use sov_modules_stf_blueprint::StfBlueprint;
 use sov_risc0_adapter::guest::Risc0Guest;
 use sov_state::ZkStorage;
 use stf_starter::runtime::Runtime;
-use stf_starter::StfVerifier;
+use stf_starter::{
+    guest_namespace_record, namespace_id, StfVerifier, GUEST_NAMESPACE_RECORD_LEN,
+    ROLLUP_BATCH_NAMESPACE,
+};
 
-/// The namespace for the rollup on Celestia. Must be kept in sync with the "rollup/src/lib.rs"
-const ROLLUP_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-celest");
+/// The namespace for the rollup on Celestia, shared with the node through `constants.json`.
+const ROLLUP_NAMESPACE: Namespace = Namespace::const_v0(namespace_id(ROLLUP_BATCH_NAMESPACE));
+
+/// The namespace, embedded in the ELF for the node to check that it matches its own.
+#[used]
+static NAMESPACE_RECORD: [u8; GUEST_NAMESPACE_RECORD_LEN] =
+    guest_namespace_record(ROLLUP_BATCH_NAMESPACE);
 
 risc0_zkvm::guest::entry!(main);
 
 pub fn main() {
+    // Keeps the namespace record from being removed by the linker
+    core::hint::black_box(&NAMESPACE_RECORD);
     let guest = Risc0Guest::new();
     let storage = ZkStorage::new();
     let stf: StfBlueprint<ZkDefaultContext, _, _, Runtime<_, _>, BasicKernel<_, _>> =
@@ -1,147 +1,723 @@
 //! This binary runs the rollup full node.
 
-use anyhow::Context;
+use anyhow::{bail, Context as _};
 use clap::Parser;
+use serde::Deserialize;
 #[cfg(feature = "celestia_da")]
-use sov_celestia_adapter::CelestiaConfig;
+use sov_celestia_adapter::verifier::CelestiaSpec;
 #[cfg(feature = "mock_da")]
-use sov_mock_da::MockDaConfig;
-use sov_modules_rollup_blueprint::{Rollup, RollupBlueprint};
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{Context, DaSpec, Spec};
+use sov_modules_rollup_blueprint::RollupBlueprint;
 use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisConfig;
 use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisPaths;
+use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
 #[cfg(feature = "celestia_da")]
-use sov_rollup_starter::celestia_rollup::CelestiaRollup;
+use sov_rollup_starter::celestia_rollup::CelestiaDa;
+use sov_rollup_starter::consistency::verify_storage;
+use sov_rollup_starter::export::{export_state, open_storage};
+use sov_rollup_starter::genesis::{
+    account_addresses, genesis_hash, parse_balance, parse_genesis_value, read_genesis_sections,
+    write_genesis_sections, GenesisBuilder,
+};
+use sov_rollup_starter::metrics::MetricsConfig;
 #[cfg(feature = "mock_da")]
-use sov_rollup_starter::mock_rollup::MockRollup;
-use sov_stf_runner::RollupProverConfig;
-use sov_stf_runner::{from_toml_path, RollupConfig};
-use std::str::FromStr;
+use sov_rollup_starter::mock_rollup::MockDa;
+use sov_rollup_starter::prover::{ProverConfig, ProverMode};
+use sov_rollup_starter::pruning::{
+    check_state_schema, db_stats, oldest_readable_version_of, prune_state, PruningConfig,
+    PruningPolicy,
+};
+use sov_rollup_starter::sequencer::read_sequencer_config;
+use sov_rollup_starter::shutdown::{run_until_shutdown, termination_signal, Stopped};
+use sov_rollup_starter::snapshot::{bootstrap_from_snapshot, create_snapshot, restore_snapshot};
+use sov_rollup_starter::starter_rollup::{DaAdapter, StarterRollup};
+use sov_rollup_starter::status::HealthConfig;
+use sov_rollup_starter::telemetry::TelemetryConfig;
+use sov_state::Storage;
+use sov_stf_runner::{from_toml_path, RollupConfig, RunnerConfig, StorageConfig};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::Runtime;
 use tracing::info;
-use tracing_subscriber::prelude::*;
-use tracing_subscriber::{fmt, EnvFilter};
 
-// config and genesis for mock da
-#[cfg(feature = "mock_da")]
-const DEFAULT_CONFIG_PATH: &str = "../../rollup_config.toml";
-#[cfg(feature = "mock_da")]
-const DEFAULT_GENESIS_PATH: &str = "../../test-data/genesis/mock/";
-#[cfg(feature = "mock_da")]
-const DEFAULT_KERNEL_GENESIS_PATH: &str = "../../test-data/genesis/mock/chain_state.json";
+/// The DA layer the rollup runs on.
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum DaLayer {
+    /// In-process mock DA, for local development.
+    #[default]
+    Mock,
+    /// Celestia, e.g. the local docker network started with `make start`.
+    Celestia,
+}
 
+impl DaLayer {
+    fn default_config_path(self) -> &'static str {
+        match self {
+            DaLayer::Mock => "../../rollup_config.toml",
+            DaLayer::Celestia => "../../celestia_rollup_config.toml",
+        }
+    }
 
+    fn default_genesis_path(self) -> &'static str {
+        match self {
+            DaLayer::Mock => "../../test-data/genesis/mock/",
+            DaLayer::Celestia => "../../test-data/genesis/celestia/",
+        }
+    }
 
-// config and genesis for local docker celestia
-#[cfg(feature = "celestia_da")]
-const DEFAULT_CONFIG_PATH: &str = "../../celestia_rollup_config.toml";
-#[cfg(feature = "celestia_da")]
-const DEFAULT_GENESIS_PATH: &str = "../../test-data/genesis/celestia/";
-#[cfg(feature = "celestia_da")]
-const DEFAULT_KERNEL_GENESIS_PATH: &str = "../../test-data/genesis/celestia/chain_state.json";
+    fn default_kernel_genesis_path(self) -> &'static str {
+        match self {
+            DaLayer::Mock => "../../test-data/genesis/mock/chain_state.json",
+            DaLayer::Celestia => "../../test-data/genesis/celestia/chain_state.json",
+        }
+    }
+}
 
+/// The `kind` key of the `[da]` section of the rollup config.
+#[derive(Deserialize)]
+struct DaKindSection {
+    da: Option<DaKind>,
+}
+
+#[derive(Deserialize)]
+struct DaKind {
+    kind: Option<DaLayer>,
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// The path to the rollup config.
-    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
-    rollup_config_path: String,
+    /// Runs a command instead of the node.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The DA layer to run on. Takes precedence over the `da.kind` key of the rollup config.
+    #[arg(long, value_enum, global = true)]
+    da: Option<DaLayer>,
+
+    /// The path to the rollup config. Defaults to the config of the selected DA layer.
+    #[arg(long, global = true)]
+    rollup_config_path: Option<String>,
+
+    /// The path to the genesis config: either a directory with a JSON file per module, or a
+    /// single `genesis.json` or `genesis.toml` file. Defaults to the genesis of the selected DA
+    /// layer.
+    #[arg(long, global = true)]
+    genesis_paths: Option<String>,
+    /// The path to the kernel genesis config. Defaults to the `chain_state` section of a single
+    /// genesis file, or to the genesis of the selected DA layer.
+    #[arg(long, global = true)]
+    kernel_genesis_paths: Option<String>,
+
+    /// The prover mode. Takes precedence over the `[prover]` section of the rollup config.
+    #[arg(long, value_enum)]
+    prover_mode: Option<ProverMode>,
+
+    /// Also verify the aggregated proofs posted to the proof namespace of the DA layer. The last
+    /// verified proof is reported by `node_syncStatus`.
+    #[arg(long)]
+    verify_da_proofs: bool,
+
+    /// On SIGINT or SIGTERM, how long to wait for the current slot to finish, then for the
+    /// processed slots to be proven, and then for the remaining proofs to be published, before
+    /// exiting with an error.
+    #[arg(long, default_value_t = 60)]
+    shutdown_timeout_secs: u64,
+
+    /// When the storage was written with another `start_height` than the one of the rollup
+    /// config, resume from the DA heights of the storage instead of failing. It does not roll
+    /// back a ledger and a state that end at different heights.
+    #[arg(long)]
+    repair: bool,
+
+    /// A snapshot written by `node snapshot create` to restore when the storage is empty, so a
+    /// new node resumes from the height of the snapshot instead of `start_height`.
+    #[arg(long)]
+    snapshot: Option<PathBuf>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Generates, validates and hashes genesis configs.
+    #[command(subcommand)]
+    Genesis(GenesisCommand),
+    /// Exports the state of the rollup at a given height as a genesis, using `--genesis-paths`
+    /// as the genesis the rollup started from. The node must be stopped.
+    ExportState(ExportStateArgs),
+    /// Creates and restores snapshots of the storage. The node must be stopped.
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Inspects and prunes the databases of the node. The node must be stopped.
+    #[command(subcommand)]
+    Db(DbCommand),
+}
 
-    /// The path to the genesis config.
-    #[arg(long, default_value = DEFAULT_GENESIS_PATH)]
-    genesis_paths: String,
-    /// The path to the kernel genesis config.
-    #[arg(long, default_value = DEFAULT_KERNEL_GENESIS_PATH)]
-    kernel_genesis_paths: String,
+#[derive(clap::Subcommand, Debug)]
+enum DbCommand {
+    /// Prints the on-disk size of the databases, by column family.
+    Stats,
+    /// Prunes the state according to the `[storage.pruning]` section of the rollup config.
+    Prune,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum SnapshotCommand {
+    /// Writes a snapshot of the storage, at its last height, to a new directory.
+    Create(CreateSnapshotArgs),
+    /// Restores a snapshot next to the storage, which must be empty, verifies it, then moves it
+    /// to the storage.
+    Restore(RestoreSnapshotArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct CreateSnapshotArgs {
+    /// The directory to write the snapshot to.
+    #[arg(long)]
+    output: PathBuf,
+    /// The height the snapshot must end at. Snapshots are written at the last height of the
+    /// storage, so this only checks that the node was stopped at this height.
+    #[arg(long)]
+    height: Option<u64>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RestoreSnapshotArgs {
+    /// The directory of the snapshot.
+    #[arg(long)]
+    input: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct ExportStateArgs {
+    /// The rollup height to export.
+    #[arg(long)]
+    height: u64,
+    /// Where to write the genesis: a `.json` or `.toml` file, or a directory.
+    #[arg(long)]
+    output: PathBuf,
+    /// Export the accounts that sent transactions with a nonce of 0, which lets their
+    /// transactions be replayed on the new chain. Without it, the export fails if any account
+    /// sent transactions.
+    #[arg(long)]
+    reset_nonces: bool,
+}
+
+/// The `[storage]` section of the rollup config.
+#[derive(Deserialize)]
+struct StorageSection {
+    storage: StorageConfig,
+}
+
+/// The `[runner]` section of the rollup config.
+#[derive(Deserialize)]
+struct RunnerSection {
+    runner: RunnerConfig,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum GenesisCommand {
+    /// Generates a genesis, using `--genesis-paths` as the template of the sections that are not
+    /// generated, then validates it and prints the hash of its configuration.
+    Generate(GenerateGenesisArgs),
+    /// Validates the genesis given by `--genesis-paths` and prints the hash of its configuration.
+    Hash,
+}
+
+#[derive(clap::Args, Debug)]
+struct GenerateGenesisArgs {
+    /// Where to write the genesis: a `.json` or `.toml` file, or a directory.
+    #[arg(long)]
+    output: PathBuf,
+    /// The rollup address of the preferred sequencer, which also mints the tokens and receives
+    /// the fees and the rewards.
+    #[arg(long)]
+    sequencer_rollup_address: String,
+    /// The DA address of the preferred sequencer.
+    #[arg(long)]
+    sequencer_da_address: String,
+    /// The hex-encoded public key of an account to register at genesis.
+    #[arg(long = "account")]
+    accounts: Vec<String>,
+    /// A genesis balance of the gas token, as `<address>=<amount>`.
+    #[arg(long = "balance")]
+    balances: Vec<String>,
+    /// The name of the gas token. Its address must match `GAS_TOKEN_ADDRESS`.
+    #[arg(long, default_value = "sov-gas-token")]
+    token_name: String,
+    /// The salt of the gas token.
+    #[arg(long, default_value_t = 0)]
+    token_salt: u64,
+    /// The name of the token the sequencer locks to register.
+    #[arg(long, default_value = "sov-demo-token")]
+    lock_token_name: String,
+    /// The salt of the token the sequencer locks.
+    #[arg(long, default_value_t = 0)]
+    lock_token_salt: u64,
+    /// The genesis balance of the sequencer in the token it locks.
+    #[arg(long, default_value_t = 100000000)]
+    lock_token_balance: u64,
+    /// The amount of tokens the sequencer locks to register.
+    #[arg(long, default_value_t = 50)]
+    coins_to_lock: u64,
+    /// The account the sequencer rewards are paid from.
+    #[arg(long)]
+    reward_pool_address: String,
+    /// The genesis balance of the reward pool in the gas token.
+    #[arg(long, default_value_t = 1000000000)]
+    reward_pool_balance: u64,
+    /// The price of a unit of gas.
+    #[arg(long, default_value_t = 0)]
+    gas_price: u64,
+    /// The number of slots kept by the `chain_history` module.
+    #[arg(long, default_value_t = 1000)]
+    window_size: u64,
+}
+
+/// The state root the rollup proofs commit to.
+type StateRoot = <<DefaultContext as Spec>::Storage as Storage>::Root;
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    // Initializing logging
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        //.with(EnvFilter::from_default_env())
-        .with(EnvFilter::from_str("info,hyper=info").unwrap())
-        .init();
-
     let args = Args::parse();
-    let rollup_config_path = args.rollup_config_path.as_str();
+    let rollup_config_path = args
+        .rollup_config_path
+        .unwrap_or_else(|| args.da.unwrap_or_default().default_config_path().to_owned());
 
-    let genesis_paths = args.genesis_paths.as_str();
-    let kernel_genesis_paths = args.kernel_genesis_paths.as_str();
+    // The `genesis` commands can be used without a rollup config
+    let has_rollup_config = Path::new(&rollup_config_path).exists();
+    let telemetry = if has_rollup_config {
+        TelemetryConfig::from_toml_path(&rollup_config_path)?.unwrap_or_default()
+    } else {
+        TelemetryConfig::default()
+    };
+    let _log_guard = telemetry.init_logging()?;
+    let da = match args.da {
+        Some(da) => da,
+        None if has_rollup_config => {
+            let section: DaKindSection = from_toml_path(&rollup_config_path)
+                .context("Failed to read the `[da]` section of the rollup configuration")?;
+            section.da.and_then(|da| da.kind).unwrap_or_default()
+        }
+        None => DaLayer::default(),
+    };
 
-    let rollup = new_rollup(
-        &GenesisPaths::from_dir(genesis_paths),
-        &BasicKernelGenesisPaths {
-            chain_state: kernel_genesis_paths.into(),
-        },
-        rollup_config_path,
-        RollupProverConfig::Execute,
-    )
-    .await?;
-    rollup.run().await
-}
+    let genesis_paths = args
+        .genesis_paths
+        .unwrap_or_else(|| da.default_genesis_path().to_owned());
+    let rt_genesis_paths = GenesisPaths::from_path(&genesis_paths);
+    // A single genesis file carries the kernel genesis in its `chain_state` section
+    let kernel_genesis_paths = match (args.kernel_genesis_paths, &rt_genesis_paths) {
+        (Some(path), _) => Some(path),
+        (None, GenesisPaths::File(_)) => None,
+        (None, GenesisPaths::Modules(_)) => Some(da.default_kernel_genesis_path().to_owned()),
+    }
+    .map(|path| BasicKernelGenesisPaths {
+        chain_state: path.into(),
+    });
 
-#[cfg(feature = "mock_da")]
-async fn new_rollup(
-    rt_genesis_paths: &GenesisPaths,
-    kernel_genesis_paths: &BasicKernelGenesisPaths,
-    rollup_config_path: &str,
-    prover_config: RollupProverConfig,
-) -> Result<Rollup<MockRollup>, anyhow::Error> {
-    info!("Reading rollup config from {rollup_config_path:?}");
+    if let Some(command) = args.command {
+        return match da {
+            #[cfg(feature = "mock_da")]
+            DaLayer::Mock => run_command::<MockDaSpec>(
+                command,
+                rt_genesis_paths,
+                kernel_genesis_paths,
+                &rollup_config_path,
+            ),
+            #[cfg(feature = "celestia_da")]
+            DaLayer::Celestia => run_command::<CelestiaSpec>(
+                command,
+                rt_genesis_paths,
+                kernel_genesis_paths,
+                &rollup_config_path,
+            ),
+            #[allow(unreachable_patterns)]
+            da => bail!("The node was built without support for {:?} DA", da),
+        };
+    }
 
-    let rollup_config: RollupConfig<MockDaConfig> =
-        from_toml_path(rollup_config_path).context("Failed to read rollup configuration")?;
+    let prover_mode = match args.prover_mode {
+        Some(mode) => mode,
+        None => ProverConfig::from_toml_path(&rollup_config_path)?
+            .map(|config| config.mode)
+            .unwrap_or_default(),
+    };
 
-    let mock_rollup = MockRollup {};
+    if let Some(metrics) = MetricsConfig::from_toml_path(&rollup_config_path)? {
+        metrics.install()?;
+    }
 
-    let kernel_genesis = BasicKernelGenesisConfig {
-        chain_state: serde_json::from_str(
+    let shutdown_timeout = Duration::from_secs(args.shutdown_timeout_secs);
+    info!("Running the rollup on {:?} DA", da);
+    info!("Running the prover in {:?} mode", prover_mode);
+    match da {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => {
+            let (starter_rollup, rollup_config) = prepare_rollup::<MockDa>(
+                &rt_genesis_paths,
+                &rollup_config_path,
+                prover_mode,
+                args.verify_da_proofs,
+                args.repair,
+                args.snapshot.as_deref(),
+            )
+            .await?;
+            run_rollup(
+                starter_rollup,
+                rollup_config,
+                &rt_genesis_paths,
+                kernel_genesis_paths.as_ref(),
+                &rollup_config_path,
+                prover_mode,
+                shutdown_timeout,
+            )
+            .await
+        }
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => {
+            let (starter_rollup, rollup_config) = prepare_rollup::<CelestiaDa>(
+                &rt_genesis_paths,
+                &rollup_config_path,
+                prover_mode,
+                args.verify_da_proofs,
+                args.repair,
+                args.snapshot.as_deref(),
+            )
+            .await?;
+            run_rollup(
+                starter_rollup,
+                rollup_config,
+                &rt_genesis_paths,
+                kernel_genesis_paths.as_ref(),
+                &rollup_config_path,
+                prover_mode,
+                shutdown_timeout,
+            )
+            .await
+        }
+        #[allow(unreachable_patterns)]
+        da => bail!("The node was built without support for {:?} DA", da),
+    }
+}
+
+fn read_kernel_genesis<C: Context, Da: DaSpec>(
+    rt_genesis_paths: &GenesisPaths,
+    kernel_genesis_paths: Option<&BasicKernelGenesisPaths>,
+) -> Result<BasicKernelGenesisConfig<C, Da>, anyhow::Error> {
+    let chain_state = match kernel_genesis_paths {
+        Some(kernel_genesis_paths) => serde_json::from_str(
             &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
                 .context("Failed to read chain state")?,
         )?,
+        None => {
+            let Some(chain_state) = rt_genesis_paths.read_chain_state_config()? else {
+                bail!("No kernel genesis config was given");
+            };
+            serde_json::from_value(chain_state)
+                .context("Failed to read the `chain_state` section of the genesis")?
+        }
     };
+    Ok(BasicKernelGenesisConfig { chain_state })
+}
 
-    mock_rollup
-        .create_new_rollup(
-            rt_genesis_paths,
-            kernel_genesis,
-            rollup_config,
-            prover_config,
-        )
-        .await
+fn run_genesis_command<Da: DaSpec>(
+    command: GenesisCommand,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: Option<BasicKernelGenesisPaths>,
+) -> Result<(), anyhow::Error> {
+    let (rt_genesis_paths, kernel_genesis_paths) = match command {
+        GenesisCommand::Hash => (rt_genesis_paths, kernel_genesis_paths),
+        GenesisCommand::Generate(args) => {
+            let template = read_genesis_sections(
+                &rt_genesis_paths,
+                kernel_genesis_paths
+                    .as_ref()
+                    .map(|paths| Path::new(&paths.chain_state)),
+            )?;
+            let builder = GenesisBuilder::<Da> {
+                sequencer_rollup_address: parse_genesis_value(&args.sequencer_rollup_address)?,
+                sequencer_da_address: parse_genesis_value(&args.sequencer_da_address)?,
+                accounts: args
+                    .accounts
+                    .iter()
+                    .map(|account| parse_genesis_value(account))
+                    .collect::<Result<_, _>>()?,
+                balances: args
+                    .balances
+                    .iter()
+                    .map(|balance| parse_balance(balance))
+                    .collect::<Result<_, _>>()?,
+                token_name: args.token_name,
+                token_salt: args.token_salt,
+                lock_token_name: args.lock_token_name,
+                lock_token_salt: args.lock_token_salt,
+                lock_token_balance: args.lock_token_balance,
+                coins_to_lock: args.coins_to_lock,
+                reward_pool_address: parse_genesis_value(&args.reward_pool_address)?,
+                reward_pool_balance: args.reward_pool_balance,
+                gas_price: args.gas_price,
+                window_size: args.window_size,
+            };
+
+            println!(
+                "Gas token `{}`: {}",
+                builder.token_name,
+                builder.token_address()
+            );
+            println!(
+                "Lock token `{}`: {}",
+                builder.lock_token_name,
+                builder.lock_token_address()
+            );
+            for address in account_addresses(&builder.accounts) {
+                println!("Account: {address}");
+            }
+
+            write_genesis_sections(&builder.build(&template)?, &args.output)?;
+            written_genesis_paths(&args.output)
+        }
+    };
+
+    check_genesis::<Da>(&rt_genesis_paths, kernel_genesis_paths)
 }
 
-#[cfg(feature = "celestia_da")]
-async fn new_rollup(
-    rt_genesis_paths: &GenesisPaths,
-    kernel_genesis_paths: &BasicKernelGenesisPaths,
+fn run_command<Da: DaSpec>(
+    command: Command,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: Option<BasicKernelGenesisPaths>,
     rollup_config_path: &str,
-    prover_config: RollupProverConfig,
-) -> Result<Rollup<CelestiaRollup>, anyhow::Error> {
-    info!(
-        "Starting celestia rollup with config {}",
-        rollup_config_path
+) -> Result<(), anyhow::Error> {
+    match command {
+        Command::Genesis(command) => {
+            run_genesis_command::<Da>(command, rt_genesis_paths, kernel_genesis_paths)
+        }
+        Command::ExportState(args) => {
+            let section: StorageSection = from_toml_path(rollup_config_path)
+                .context("Failed to read the `[storage]` section of the rollup configuration")?;
+            let oldest_readable_version = oldest_readable_version_of(&section.storage.path)?;
+            // The state after slot `N` is stored as version `N + 1`
+            if args.height + 1 < oldest_readable_version {
+                bail!(
+                    "Cannot export height {}, the state was pruned below version {}",
+                    args.height,
+                    oldest_readable_version
+                );
+            }
+            let storage = open_storage::<Da>(&section.storage.path)?;
+
+            let template = read_genesis_sections(
+                &rt_genesis_paths,
+                kernel_genesis_paths
+                    .as_ref()
+                    .map(|paths| Path::new(&paths.chain_state)),
+            )?;
+            let sections = export_state::<Da>(&storage, args.height, &template, args.reset_nonces)?;
+            write_genesis_sections(&sections, &args.output)?;
+
+            let (rt_genesis_paths, kernel_genesis_paths) = written_genesis_paths(&args.output);
+            check_genesis::<Da>(&rt_genesis_paths, kernel_genesis_paths)
+        }
+        Command::Snapshot(command) => {
+            let storage: StorageSection = from_toml_path(rollup_config_path)
+                .context("Failed to read the `[storage]` section of the rollup configuration")?;
+            let runner: RunnerSection = from_toml_path(rollup_config_path)
+                .context("Failed to read the `[runner]` section of the rollup configuration")?;
+            let manifest = match command {
+                SnapshotCommand::Create(args) => create_snapshot::<Da>(
+                    &storage.storage.path,
+                    runner.runner.start_height,
+                    args.height,
+                    &args.output,
+                )?,
+                SnapshotCommand::Restore(args) => restore_snapshot::<Da>(
+                    &args.input,
+                    &storage.storage.path,
+                    runner.runner.start_height,
+                )?,
+            };
+            println!("{}", serde_json::to_string_pretty(&manifest)?);
+            Ok(())
+        }
+        Command::Db(command) => {
+            let section: StorageSection = from_toml_path(rollup_config_path)
+                .context("Failed to read the `[storage]` section of the rollup configuration")?;
+            let storage_path = &section.storage.path;
+            match command {
+                DbCommand::Stats => {
+                    for database in db_stats(storage_path)? {
+                        println!("{}: {} bytes", database.name, database.size);
+                        for cf in database.column_families {
+                            println!(
+                                "  {}: {} bytes, ~{} keys",
+                                cf.name, cf.size, cf.estimated_keys
+                            );
+                        }
+                    }
+                }
+                DbCommand::Prune => {
+                    let policy = PruningConfig::from_toml_path(rollup_config_path)?.policy;
+                    let Some(versions) = policy.versions_to_keep(storage_path)? else {
+                        println!("The pruning policy keeps every version");
+                        return Ok(());
+                    };
+                    check_state_schema(storage_path)?;
+                    match prune_state(storage_path, versions)? {
+                        Some(pruned) => println!(
+                            "Pruned {} state values, the state is readable from version {}",
+                            pruned.deleted_values, pruned.oldest_readable_version
+                        ),
+                        None => println!("Nothing to prune"),
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The paths of a genesis written by [`write_genesis_sections`].
+fn written_genesis_paths(output: &Path) -> (GenesisPaths, Option<BasicKernelGenesisPaths>) {
+    let genesis_paths = GenesisPaths::from_path(output);
+    // The `chain_state` of a genesis directory is written next to the module files
+    let kernel_genesis_paths = match genesis_paths {
+        GenesisPaths::Modules(_) => Some(BasicKernelGenesisPaths {
+            chain_state: output.join("chain_state.json"),
+        }),
+        GenesisPaths::File(_) => None,
+    };
+    (genesis_paths, kernel_genesis_paths)
+}
+
+/// Validates a genesis and prints its hash.
+fn check_genesis<Da: DaSpec>(
+    rt_genesis_paths: &GenesisPaths,
+    kernel_genesis_paths: Option<BasicKernelGenesisPaths>,
+) -> Result<(), anyhow::Error> {
+    Runtime::<DefaultContext, Da>::genesis_config(rt_genesis_paths)?;
+    read_kernel_genesis::<DefaultContext, Da>(rt_genesis_paths, kernel_genesis_paths.as_ref())?;
+
+    let sections = read_genesis_sections(
+        rt_genesis_paths,
+        kernel_genesis_paths
+            .as_ref()
+            .map(|paths| Path::new(&paths.chain_state)),
+    )?;
+    println!(
+        "Genesis config hash: {}",
+        hex::encode(genesis_hash(&sections))
     );
+    Ok(())
+}
 
-    let rollup_config: RollupConfig<CelestiaConfig> =
+/// Prepares the rollup on the DA layer `Da`, after checking and bootstrapping its storage, and
+/// returns its config.
+async fn prepare_rollup<Da: DaAdapter>(
+    rt_genesis_paths: &GenesisPaths,
+    rollup_config_path: &str,
+    prover_mode: ProverMode,
+    verify_da_proofs: bool,
+    repair: bool,
+    snapshot: Option<&Path>,
+) -> Result<(StarterRollup<Da>, RollupConfig<Da::DaConfig>), anyhow::Error> {
+    info!("Reading rollup config from {rollup_config_path:?}");
+    StarterRollup::<Da>::check_guest(prover_mode)?;
+
+    let mut rollup_config: RollupConfig<Da::DaConfig> =
         from_toml_path(rollup_config_path).context("Failed to read rollup configuration")?;
+    if let Some(snapshot) = snapshot {
+        bootstrap_from_snapshot::<Da::DaSpec>(
+            snapshot,
+            &rollup_config.storage.path,
+            rollup_config.runner.start_height,
+        )?;
+    }
+    rollup_config.runner.start_height = verify_storage::<Da::DaSpec>(
+        &rollup_config.storage.path,
+        rollup_config.runner.start_height,
+        repair,
+    )?;
 
-    let kernel_genesis = BasicKernelGenesisConfig {
-        chain_state: serde_json::from_str(
-            &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
-                .context("Failed to read chain state")?,
-        )?,
-    };
+    let sequencer = read_sequencer_config::<Da::DaSpec>(rollup_config_path, rt_genesis_paths)?;
+    if let Some(sequencer) = &sequencer {
+        Da::check_sequencer(&rollup_config.da, sequencer.da_address()).await?;
+    }
+
+    let mut starter_rollup = StarterRollup::<Da>::new(sequencer);
+    starter_rollup.health = HealthConfig::from_toml_path(rollup_config_path)?;
+    #[cfg(feature = "experimental")]
+    {
+        starter_rollup.eth =
+            sov_rollup_starter::eth::EthConfig::from_toml_path(rollup_config_path)?;
+    }
+
+    starter_rollup.open_da_service(&rollup_config).await?;
+    if verify_da_proofs {
+        let follower = starter_rollup.create_proof_follower(&rollup_config)?;
+        tokio::spawn(follower.run::<StateRoot>());
+    }
+    Ok((starter_rollup, rollup_config))
+}
+
+/// Runs the rollup until the node is terminated.
+///
+/// Unless the pruning policy keeps every version, the runner is paused every
+/// `pruning_interval` slots: the databases are closed, the state is pruned, and a new rollup
+/// resumes on the same DA service.
+async fn run_rollup<Da: DaAdapter>(
+    starter_rollup: StarterRollup<Da>,
+    rollup_config: RollupConfig<Da::DaConfig>,
+    rt_genesis_paths: &GenesisPaths,
+    kernel_genesis_paths: Option<&BasicKernelGenesisPaths>,
+    rollup_config_path: &str,
+    prover_mode: ProverMode,
+    shutdown_timeout: Duration,
+) -> Result<(), anyhow::Error> {
+    let pruning = PruningConfig::from_toml_path(rollup_config_path)?;
+    let storage_path = rollup_config.storage.path.clone();
+    let shutdown = starter_rollup.shutdown();
+    let mut signal = Box::pin(termination_signal());
+
+    // Reading the whole state, the schema is only checked once, before the first pruning
+    if pruning.policy != PruningPolicy::Archive {
+        check_state_schema(&storage_path)?;
+    }
+    loop {
+        pruning.policy.apply(&storage_path)?;
+        if let Some(pause_height) =
+            pruning.pause_height(&storage_path, rollup_config.runner.start_height)?
+        {
+            shutdown.pause_after(pause_height);
+        }
 
-    let mock_rollup = CelestiaRollup {};
-    mock_rollup
-        .create_new_rollup(
-            rt_genesis_paths,
-            kernel_genesis,
-            rollup_config,
-            prover_config,
+        let kernel_genesis = read_kernel_genesis(rt_genesis_paths, kernel_genesis_paths)?;
+        let rpc_config = rollup_config.runner.rpc_config.clone();
+        let rollup = starter_rollup
+            .create_new_rollup(
+                rt_genesis_paths,
+                kernel_genesis,
+                rollup_config.clone(),
+                prover_mode.into(),
+            )
+            .await?;
+        match run_until_shutdown(
+            rollup,
+            &rpc_config,
+            None,
+            &shutdown,
+            &mut signal,
+            shutdown_timeout,
         )
-        .await
+        .await?
+        {
+            Stopped::Shutdown => return Ok(()),
+            Stopped::Paused => {
+                info!("Pruning the state every {} slots", pruning.interval);
+                shutdown.resume();
+            }
+        }
+    }
 }
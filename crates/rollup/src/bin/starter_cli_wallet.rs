@@ -1,19 +1,48 @@
 //! This binary defines a cli wallet for interacting
 //! with the rollup.
 
+use anyhow::bail;
 use sov_modules_api::cli::{FileNameArg, JsonStringArg};
 use sov_modules_rollup_blueprint::WalletBlueprint;
 #[cfg(feature = "celestia_da")]
-use sov_rollup_starter::celestia_rollup::CelestiaRollup as StarterRollup;
+use sov_rollup_starter::celestia_rollup::CelestiaRollup;
 #[cfg(feature = "mock_da")]
-use sov_rollup_starter::mock_rollup::MockRollup as StarterRollup;
+use sov_rollup_starter::mock_rollup::MockRollup;
 use stf_starter::runtime::RuntimeSubcommand;
 
+/// Selects the DA layer of the wallet, as `node --da` does for the node: `mock` (the default) or
+/// `celestia`. The arguments of the wallet are all passed to the SDK wallet, hence a variable.
+const DA_ENV_VAR: &str = "SOV_WALLET_DA";
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    StarterRollup::run_wallet::<
-        RuntimeSubcommand<FileNameArg, _, _>,
-        RuntimeSubcommand<JsonStringArg, _, _>,
-    >()
-    .await
+    let da = std::env::var(DA_ENV_VAR).unwrap_or_else(|_| "mock".to_owned());
+    match da.as_str() {
+        #[cfg(feature = "mock_da")]
+        "mock" => {
+            MockRollup::run_wallet::<
+                RuntimeSubcommand<FileNameArg, _, _>,
+                RuntimeSubcommand<JsonStringArg, _, _>,
+            >()
+            .await
+        }
+        #[cfg(feature = "celestia_da")]
+        "celestia" => {
+            CelestiaRollup::run_wallet::<
+                RuntimeSubcommand<FileNameArg, _, _>,
+                RuntimeSubcommand<JsonStringArg, _, _>,
+            >()
+            .await
+        }
+        other => bail!(
+            "Unsupported DA layer `{other}` in {DA_ENV_VAR}: this wallet was built for {}",
+            [
+                #[cfg(feature = "mock_da")]
+                "`mock`",
+                #[cfg(feature = "celestia_da")]
+                "`celestia`",
+            ]
+            .join(", ")
+        ),
+    }
 }
@@ -1,142 +1,116 @@
 #![deny(missing_docs)]
-//! StarterRollup provides a minimal self-contained rollup implementation
+//! The Celestia DA adapter of the [`StarterRollup`].
 
+use anyhow::Context as _;
 use async_trait::async_trait;
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClientBuilder};
+use jsonrpsee::rpc_params;
 use sov_celestia_adapter::types::Namespace;
 use sov_celestia_adapter::verifier::{CelestiaSpec, CelestiaVerifier, RollupParams};
 use sov_celestia_adapter::{CelestiaConfig, CelestiaService};
-use sov_modules_api::default_context::{DefaultContext, ZkDefaultContext};
-use sov_modules_api::Address;
-use sov_modules_api::Spec;
-use sov_modules_rollup_blueprint::RollupBlueprint;
-use sov_modules_stf_blueprint::kernels::basic::BasicKernel;
-use sov_modules_stf_blueprint::StfBlueprint;
-use sov_prover_storage_manager::ProverStorageManager;
-use sov_risc0_adapter::host::Risc0Host;
-use sov_rollup_interface::zk::ZkvmHost;
-use sov_state::config::Config as StorageConfig;
-use sov_state::Storage;
-use sov_state::{DefaultStorageSpec, ZkStorage};
-use sov_stf_runner::ParallelProverService;
+use sov_modules_api::DaSpec;
 use sov_stf_runner::RollupConfig;
-use sov_stf_runner::RollupProverConfig;
-use stf_starter::Runtime;
+use stf_starter::{
+    namespace_id, read_guest_namespace, ROLLUP_BATCH_NAMESPACE,
+    ROLLUP_PROOF_NAMESPACE as PROOF_NAMESPACE,
+};
 
-/// The namespace for the rollup on Celestia.
-const ROLLUP_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-celest");
+use crate::starter_rollup::{DaAdapter, StarterRollup};
 
-/// The rollup stores the zk proofs in the namespace b"sov-test-p" on Celestia.
-const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-test-p");
+/// The namespace for the rollup on Celestia, shared with the guest through `constants.json`.
+const ROLLUP_NAMESPACE: Namespace = Namespace::const_v0(namespace_id(ROLLUP_BATCH_NAMESPACE));
 
-/// Rollup with [`CelestiaDaService`].
-pub struct CelestiaRollup {}
+/// The namespace the rollup stores the zk proofs in on Celestia.
+const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(namespace_id(PROOF_NAMESPACE));
+
+/// Rollup with [`CelestiaService`].
+pub type CelestiaRollup = StarterRollup<CelestiaDa>;
+
+/// The Celestia DA layer.
+pub struct CelestiaDa;
 
-/// This is the place, where all the rollup components come together and
-/// they can be easily swapped with alternative implementations as needed.
 #[async_trait]
-impl RollupBlueprint for CelestiaRollup {
+impl DaAdapter for CelestiaDa {
     type DaService = CelestiaService;
     type DaSpec = CelestiaSpec;
     type DaConfig = CelestiaConfig;
-    type Vm = Risc0Host<'static>;
-
-    type ZkContext = ZkDefaultContext;
-    type NativeContext = DefaultContext;
-
-    type StorageManager = ProverStorageManager<CelestiaSpec, DefaultStorageSpec>;
-    type ZkRuntime = Runtime<Self::ZkContext, Self::DaSpec>;
-
-    type NativeRuntime = Runtime<Self::NativeContext, Self::DaSpec>;
-
-    type NativeKernel = BasicKernel<Self::NativeContext, Self::DaSpec>;
-    type ZkKernel = BasicKernel<Self::ZkContext, Self::DaSpec>;
-
-    type ProverService = ParallelProverService<
-        <<Self::NativeContext as Spec>::Storage as Storage>::Root,
-        <<Self::NativeContext as Spec>::Storage as Storage>::Witness,
-        Self::DaService,
-        Self::Vm,
-        StfBlueprint<
-            Self::ZkContext,
-            Self::DaSpec,
-            <Self::Vm as ZkvmHost>::Guest,
-            Self::ZkRuntime,
-            Self::ZkKernel,
-        >,
-    >;
 
-    fn create_rpc_methods(
-        &self,
-        storage: &<Self::NativeContext as sov_modules_api::Spec>::Storage,
-        ledger_db: &sov_db::ledger_db::LedgerDB,
-        da_service: &Self::DaService,
-    ) -> Result<jsonrpsee::RpcModule<()>, anyhow::Error> {
-        // TODO set the sequencer address
-        let sequencer = Address::new([0; 32]);
-
-        #[allow(unused_mut)]
-        let mut rpc_methods = sov_modules_rollup_blueprint::register_rpc::<
-            Self::NativeRuntime,
-            Self::NativeContext,
-            Self::DaService,
-        >(storage, ledger_db, da_service, sequencer)?;
-
-        #[cfg(feature = "experimental")]
-        crate::eth::register_ethereum::<Self::DaService>(
-            da_service.clone(),
-            storage.clone(),
-            &mut rpc_methods,
-        )?;
-
-        Ok(rpc_methods)
+    const GUEST_ELF: &'static [u8] = risc0_starter::ROLLUP_ELF;
+    const GUEST_ID: [u32; 8] = risc0_starter::ROLLUP_ID;
+
+    /// The namespace is compiled into the guest as a constant, so a guest built with a different
+    /// `constants.json` reads the batches of another rollup. The guest embeds the namespace in
+    /// its ELF, which is where it is read back from.
+    fn check_guest() -> anyhow::Result<()> {
+        // Built with `SKIP_GUEST_BUILD`, the node cannot run the guest at all
+        if Self::GUEST_ELF.is_empty() {
+            return Ok(());
+        }
+        let guest_namespace = read_guest_namespace(Self::GUEST_ELF)
+            .context("The celestia guest ELF does not embed its rollup namespace")?;
+        if guest_namespace != namespace_id(ROLLUP_BATCH_NAMESPACE) {
+            anyhow::bail!(
+                "The celestia guest was built for the rollup namespace {:?}, not {:?}: rebuild it \
+                after changing `constants.json`",
+                String::from_utf8_lossy(&guest_namespace),
+                ROLLUP_BATCH_NAMESPACE
+            );
+        }
+        Ok(())
     }
 
     async fn create_da_service(
-        &self,
         rollup_config: &RollupConfig<Self::DaConfig>,
-    ) -> Self::DaService {
-        CelestiaService::new(
+    ) -> anyhow::Result<Self::DaService> {
+        Ok(CelestiaService::new(
             rollup_config.da.clone(),
             RollupParams {
                 rollup_batch_namespace: ROLLUP_NAMESPACE,
                 rollup_proof_namespace: ROLLUP_PROOF_NAMESPACE,
             },
         )
-        .await
+        .await)
     }
 
-    async fn create_prover_service(
-        &self,
-        prover_config: RollupProverConfig,
-        rollup_config: &RollupConfig<Self::DaConfig>,
-        _da_service: &Self::DaService,
-    ) -> Self::ProverService {
-        let vm = Risc0Host::new(risc0_starter::ROLLUP_ELF);
-        let zk_stf = StfBlueprint::new();
-        let zk_storage = ZkStorage::new();
-
-        let da_verifier = CelestiaVerifier {
+    fn create_da_verifier() -> CelestiaVerifier {
+        CelestiaVerifier {
             rollup_namespace: ROLLUP_NAMESPACE,
-        };
-
-        ParallelProverService::new_with_default_workers(
-            vm,
-            zk_stf,
-            da_verifier,
-            prover_config,
-            zk_storage,
-            rollup_config.prover_service,
-        )
+        }
     }
 
-    fn create_storage_manager(
-        &self,
-        rollup_config: &sov_stf_runner::RollupConfig<Self::DaConfig>,
-    ) -> Result<Self::StorageManager, anyhow::Error> {
-        let storage_config = StorageConfig {
-            path: rollup_config.storage.path.clone(),
-        };
-        ProverStorageManager::new(storage_config)
+    /// The light node signs the blobs it submits with its own account, so the account must be
+    /// the DA address of the sequencer.
+    async fn check_sequencer(
+        da_config: &CelestiaConfig,
+        sequencer_da_address: &<CelestiaSpec as DaSpec>::Address,
+    ) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Bearer {}", da_config.celestia_rpc_auth_token))
+                .context("Invalid celestia_rpc_auth_token")?,
+        );
+        let client = HttpClientBuilder::default()
+            .set_headers(headers)
+            .build(&da_config.celestia_rpc_address)?;
+        let account: String = client
+            .request("state.AccountAddress", rpc_params![])
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to read the account of the celestia node at {}",
+                    da_config.celestia_rpc_address
+                )
+            })?;
+
+        if account != sequencer_da_address.to_string() {
+            anyhow::bail!(
+                "Sequencer DA address {} does not match the account {} of the celestia node",
+                sequencer_da_address,
+                account
+            );
+        }
+        Ok(())
     }
 }
-impl sov_modules_rollup_blueprint::WalletBlueprint for CelestiaRollup {}
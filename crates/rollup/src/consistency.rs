@@ -0,0 +1,179 @@
+#![deny(missing_docs)]
+//! Consistency check of the storage of a node, before it resumes.
+//!
+//! The runner resumes after the last slot of the ledger database, and derives the DA height of
+//! every slot from the `start_height` of the rollup config: slot `N` is DA block
+//! `start_height + N - 1`. The state database records, in the `chain_history` module, the height
+//! of the last slot it applied and the DA block of that slot. Before the node resumes, the
+//! check makes sure that the ledger, the state and the config agree, since resuming with another
+//! `start_height` silently builds a different chain on top of the existing state.
+
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use chain_history::ChainHistory;
+use sov_db::ledger_db::LedgerDB;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{DaSpec, WorkingSet};
+use sov_state::Storage;
+use tracing::{info, warn};
+
+use crate::export::open_storage;
+
+/// The outcome of [`check_storage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageCheck {
+    /// The storage holds no slot, the node starts from genesis.
+    Empty,
+    /// The ledger, the state and the config agree, the node resumes after `height`.
+    Consistent {
+        /// The height of the last slot.
+        height: u64,
+    },
+    /// The ledger and the state agree, but their slots were read from DA heights derived from
+    /// another `start_height`.
+    StartHeightMismatch {
+        /// The height of the last slot.
+        height: u64,
+        /// The `start_height` of the rollup config.
+        configured: u64,
+        /// The `start_height` the storage was written with.
+        stored: u64,
+    },
+    /// The ledger and the state end at different heights.
+    HeightMismatch {
+        /// The height of the last slot of the ledger.
+        ledger_height: u64,
+        /// The height of the last slot applied to the state.
+        state_height: u64,
+    },
+    /// The ledger and the state end at the same height, but with different DA blocks.
+    BlockMismatch {
+        /// The height of the last slot.
+        height: u64,
+    },
+}
+
+/// Compares the ledger and the state in the storage of a stopped node with the `start_height`
+/// of its config.
+pub fn check_storage<Da: DaSpec>(path: &Path, start_height: u64) -> anyhow::Result<StorageCheck> {
+    if !path.exists() {
+        return Ok(StorageCheck::Empty);
+    }
+
+    // Both databases are closed when they are dropped, before the node opens them
+    let ledger_db = LedgerDB::with_path(path).context("Failed to open the ledger database")?;
+    let ledger_head = ledger_db.get_head_slot()?;
+    let storage = open_storage::<Da>(path)?;
+
+    let mut working_set = WorkingSet::new(storage.clone());
+    let chain_history = ChainHistory::<DefaultContext>::default();
+    let state_height = chain_history.get_current_height(&mut working_set);
+
+    let (ledger_height, ledger_slot) = match ledger_head {
+        Some((slot_number, slot)) => (slot_number.0, slot),
+        None if state_height == 0 => return Ok(StorageCheck::Empty),
+        None => {
+            return Ok(StorageCheck::HeightMismatch {
+                ledger_height: 0,
+                state_height,
+            })
+        }
+    };
+    if ledger_height != state_height {
+        return Ok(StorageCheck::HeightMismatch {
+            ledger_height,
+            state_height,
+        });
+    }
+
+    // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+    storage
+        .get_root_hash(ledger_height + 1)
+        .with_context(|| format!("The state of height {ledger_height} is missing"))?;
+
+    let height = ledger_height;
+    // The last slot is always in the window of `chain_history`
+    let Some(record) = chain_history.get_slot(height, &mut working_set) else {
+        bail!("The state holds no record of its last slot {height}");
+    };
+    if record.da_block_hash != ledger_slot.hash {
+        return Ok(StorageCheck::BlockMismatch { height });
+    }
+
+    let Some(stored) = (record.da_height + 1).checked_sub(height) else {
+        bail!(
+            "Slot {height} was read from DA height {}, below the slot height",
+            record.da_height
+        );
+    };
+    if stored != start_height {
+        return Ok(StorageCheck::StartHeightMismatch {
+            height,
+            configured: start_height,
+            stored,
+        });
+    }
+    Ok(StorageCheck::Consistent { height })
+}
+
+/// Checks the storage of the node before it starts, and returns the `start_height` to run it
+/// with.
+///
+/// With `repair`, a node whose storage was written with another `start_height` resumes from the
+/// DA heights of its storage rather than failing. `repair` does not roll anything back: databases
+/// at different heights cannot be truncated in place, as the storage of the SDK has no way to
+/// delete committed slots or state versions, so they fail in both cases.
+pub fn verify_storage<Da: DaSpec>(
+    path: &Path,
+    start_height: u64,
+    repair: bool,
+) -> anyhow::Result<u64> {
+    match check_storage::<Da>(path, start_height)? {
+        StorageCheck::Empty => {
+            info!("The storage is empty, starting from genesis");
+            Ok(start_height)
+        }
+        StorageCheck::Consistent { height } => {
+            info!("The storage is consistent, resuming after height {height}");
+            Ok(start_height)
+        }
+        StorageCheck::StartHeightMismatch {
+            height,
+            configured,
+            stored,
+        } if repair => {
+            warn!(
+                "The storage was written with start_height {stored}, not {configured}: resuming \
+                 after height {height} with start_height {stored}. Update the rollup config."
+            );
+            Ok(stored)
+        }
+        StorageCheck::StartHeightMismatch {
+            height,
+            configured,
+            stored,
+        } => bail!(
+            "The storage at {} was written with start_height {stored}, but the rollup config has \
+             start_height {configured}: resuming after height {height} would read other DA \
+             blocks. Set start_height to {stored}, or run with --repair to use it",
+            path.display()
+        ),
+        StorageCheck::HeightMismatch {
+            ledger_height,
+            state_height,
+        } => bail!(
+            "The ledger database at {} ends at height {ledger_height}, but the state ends at \
+             height {state_height}, probably because the node was killed while committing a \
+             slot. The databases cannot be rolled back in place, even with --repair: restore a \
+             snapshot with `node snapshot restore`, or remove the storage to resync from genesis",
+            path.display()
+        ),
+        StorageCheck::BlockMismatch { height } => bail!(
+            "The ledger database at {} and the state disagree on the DA block of height {height}: \
+             restore a snapshot with `node snapshot restore`, or remove the storage to resync \
+             from genesis",
+            path.display()
+        ),
+    }
+}
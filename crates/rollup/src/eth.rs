@@ -0,0 +1,101 @@
+#![deny(missing_docs)]
+//! Ethereum JSON-RPC for the `evm` module, behind the `experimental` feature, configured by the
+//! `[eth]` section of the rollup config.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use sov_ethereum::experimental::EthRpcConfig;
+use sov_ethereum::GasPriceOracleConfig;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::Spec;
+use sov_rollup_interface::services::da::DaService;
+use sov_stf_runner::from_toml_path;
+
+/// The `[eth]` section of the rollup config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthConfig {
+    /// The key file of the rollup account signing the transactions that wrap the Ethereum
+    /// transactions.
+    pub tx_signer_key_path: PathBuf,
+    /// A file holding the hex-encoded private key of the Ethereum account `eth_sendTransaction`
+    /// signs with. Without it, the node does not serve `eth_sendTransaction`.
+    #[serde(default)]
+    pub eth_signer_key_path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct EthSection {
+    #[serde(default)]
+    eth: Option<EthConfig>,
+}
+
+impl EthConfig {
+    /// Reads the `[eth]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: EthSection =
+            from_toml_path(path).context("Failed to read the `[eth]` section")?;
+        Ok(section.eth)
+    }
+}
+
+#[derive(Deserialize)]
+struct TxSignerKey {
+    private_key: DefaultPrivateKey,
+}
+
+/// Registers the `eth_*` methods, e.g. `eth_sendRawTransaction`, `eth_call`, `eth_getBalance`
+/// and `eth_getTransactionReceipt`.
+pub(crate) fn register_ethereum<Da: DaService>(
+    da_service: Da,
+    storage: <DefaultContext as Spec>::Storage,
+    config: &EthConfig,
+    methods: &mut jsonrpsee::RpcModule<()>,
+) -> Result<(), anyhow::Error> {
+    let eth_signer = match &config.eth_signer_key_path {
+        Some(path) => read_eth_signer(path)?,
+        None => sov_ethereum::DevSigner::new(vec![]),
+    };
+    let eth_rpc_config = EthRpcConfig::<DefaultContext> {
+        min_blob_size: Some(1),
+        sov_tx_signer_priv_key: read_sov_tx_signer_priv_key(&config.tx_signer_key_path)?,
+        eth_signer,
+        gas_price_oracle_config: GasPriceOracleConfig::default(),
+    };
+
+    let mut ethereum_rpc =
+        sov_ethereum::get_ethereum_rpc::<DefaultContext, Da>(da_service, eth_rpc_config, storage);
+    if config.eth_signer_key_path.is_none() {
+        ethereum_rpc.remove_method("eth_sendTransaction");
+    }
+    methods
+        .merge(ethereum_rpc)
+        .context("Failed to merge Ethereum RPC modules")
+}
+
+fn read_sov_tx_signer_priv_key(path: &Path) -> Result<DefaultPrivateKey, anyhow::Error> {
+    let data = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read the sov tx signer private key at {}",
+            path.display()
+        )
+    })?;
+    let key: TxSignerKey =
+        serde_json::from_str(&data).context("Failed to parse the sov tx signer private key")?;
+    Ok(key.private_key)
+}
+
+fn read_eth_signer(path: &Path) -> Result<sov_ethereum::DevSigner, anyhow::Error> {
+    let data = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read the Ethereum signer key at {}",
+            path.display()
+        )
+    })?;
+    let key = secp256k1::SecretKey::from_str(data.trim().trim_start_matches("0x"))
+        .context("Invalid Ethereum signer key")?;
+    Ok(sov_ethereum::DevSigner::new(vec![key]))
+}
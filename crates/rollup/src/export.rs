@@ -0,0 +1,300 @@
+#![deny(missing_docs)]
+//! Export of the rollup state as a genesis, to start a new chain from the state of a running one.
+//!
+//! State maps cannot be enumerated, so the accounts and the token holders to export are taken
+//! from the genesis the rollup started from and from the indexes of the `indexer` module. Their
+//! values are then read from the archival state at the exported height.
+//!
+//! The export fails if some of the state cannot be carried over by the module genesis: tokens
+//! created after genesis cannot be recreated at the same address, and the balances found must add
+//! up to the supply of each token. The module genesis has no account nonces either, so the export
+//! fails if an account sent transactions, unless the nonces are explicitly reset to 0, which lets
+//! the transactions of the exported chain be replayed on the new one. Holders credited outside of
+//! the indexed transactions, such as the sequencers registered after genesis and paid from the
+//! reward pool, are not found either, and fail the export through the supply check.
+//!
+//! Only the accounts, the balances and the preferred sequencer are exported. The other sections
+//! are copied from the genesis the rollup started from, so the new chain loses:
+//! - the paid and unpaid rewards, the slashing history and the payout addresses registered after
+//!   genesis of the `sequencer_rewards` module;
+//! - the slots kept by the `chain_history` module, whose window starts over.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context as _};
+use chain_history::ChainHistory;
+use indexer::Indexer;
+use serde::de::DeserializeOwned;
+use sov_accounts::{AccountConfig, Accounts};
+use sov_bank::{Bank, BankConfig};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{DaSpec, ModuleInfo, PublicKey, Spec, WorkingSet};
+use sov_prover_storage_manager::ProverStorageManager;
+use sov_rollup_interface::storage::HierarchicalStorageManager;
+use sov_sequencer_registry::{SequencerConfig, SequencerRegistry};
+use sov_state::config::Config as StorageConfig;
+use sov_state::DefaultStorageSpec;
+use tracing::{info, warn};
+use tx_fees::{TxFees, TxFeesConfig};
+
+use crate::genesis::GenesisSections;
+
+type Address = <DefaultContext as Spec>::Address;
+type DefaultPublicKey = <DefaultContext as Spec>::PublicKey;
+
+/// Opens the state storage of a stopped node.
+pub fn open_storage<Da: DaSpec>(path: &Path) -> anyhow::Result<<DefaultContext as Spec>::Storage> {
+    let storage_config = StorageConfig {
+        path: path.to_path_buf(),
+    };
+    ProverStorageManager::<Da, DefaultStorageSpec>::new(storage_config)?
+        .create_finalized_storage()
+        .context("Failed to open the rollup storage")
+}
+
+/// Exports the state of the rollup at `height` as the sections of a genesis.
+///
+/// The `template` is the genesis the rollup started from. The sections that hold no state
+/// (such as `tx_fees` or `chain_state`) are copied from it. With `reset_nonces`, the accounts
+/// that sent transactions are exported with a nonce of 0 rather than failing the export.
+pub fn export_state<Da: DaSpec>(
+    storage: &<DefaultContext as Spec>::Storage,
+    height: u64,
+    template: &GenesisSections,
+    reset_nonces: bool,
+) -> anyhow::Result<GenesisSections> {
+    // The indexes are only kept for the latest height
+    let mut latest_state = WorkingSet::new(storage.clone());
+    let current_height =
+        ChainHistory::<DefaultContext>::default().get_current_height(&mut latest_state);
+    if height > current_height {
+        bail!("Cannot export height {height}, the rollup is at height {current_height}");
+    }
+
+    // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+    let mut working_set = WorkingSet::new(storage.clone());
+    working_set.set_archival_version(height + 1);
+
+    let accounts = export_accounts(template, reset_nonces, &mut latest_state, &mut working_set)?;
+    let account_addresses: Vec<Address> = accounts
+        .pub_keys
+        .iter()
+        .map(|pub_key| pub_key.to_address())
+        .collect();
+    let sequencer_registry = export_sequencer_registry::<Da>(template, &mut working_set)?;
+    let bank = export_bank(
+        template,
+        &account_addresses,
+        &sequencer_registry,
+        &mut latest_state,
+        &mut working_set,
+    )?;
+
+    let mut sections = template.clone();
+    sections.insert("accounts".to_owned(), serde_json::to_value(accounts)?);
+    sections.insert("bank".to_owned(), serde_json::to_value(bank)?);
+    sections.insert(
+        "sequencer_registry".to_owned(),
+        serde_json::to_value(sequencer_registry)?,
+    );
+    warn!(
+        "The rewards, slashes and payout addresses of `sequencer_rewards` and the slots of \
+         `chain_history` are not exported, the new chain starts them over"
+    );
+    Ok(sections)
+}
+
+fn template_section<T: DeserializeOwned>(
+    template: &GenesisSections,
+    section: &str,
+) -> anyhow::Result<T> {
+    let value = template
+        .get(section)
+        .ok_or_else(|| anyhow!("The genesis has no `{section}` section"))?;
+    serde_json::from_value(value.clone())
+        .with_context(|| format!("Failed to read the `{section}` genesis"))
+}
+
+/// Exports the accounts of the genesis and the accounts that sent transactions.
+fn export_accounts(
+    template: &GenesisSections,
+    reset_nonces: bool,
+    latest_state: &mut WorkingSet<DefaultContext>,
+    working_set: &mut WorkingSet<DefaultContext>,
+) -> anyhow::Result<AccountConfig<DefaultContext>> {
+    let genesis: AccountConfig<DefaultContext> = template_section(template, "accounts")?;
+    let indexer = Indexer::<DefaultContext>::default();
+    let accounts = Accounts::<DefaultContext>::default();
+
+    let mut candidates = genesis.pub_keys;
+    candidates.extend(read_all(|offset, limit| {
+        indexer.accounts(offset, limit, latest_state)
+    })?);
+
+    let mut pub_keys: Vec<DefaultPublicKey> = Vec::new();
+    let mut nonces_reset = 0;
+    for pub_key in candidates {
+        if pub_keys.contains(&pub_key) {
+            continue;
+        }
+        let account = accounts
+            .get_account(pub_key.clone(), working_set)
+            .map_err(|e| anyhow!("Failed to query the accounts module: {}", e))?;
+        match account {
+            sov_accounts::Response::AccountExists { nonce, .. } => {
+                if nonce > 0 {
+                    nonces_reset += 1;
+                }
+                pub_keys.push(pub_key);
+            }
+            sov_accounts::Response::AccountEmpty => {}
+        }
+    }
+
+    if nonces_reset > 0 {
+        if !reset_nonces {
+            bail!(
+                "{nonces_reset} accounts sent transactions, and the genesis cannot set their \
+                 nonces. Reset them to 0 to export anyway, which lets the transactions of this \
+                 chain be replayed on the new one"
+            );
+        }
+        warn!("The nonces of {nonces_reset} accounts restart at 0 in the exported genesis");
+    }
+    info!("Exported {} accounts", pub_keys.len());
+    Ok(AccountConfig { pub_keys })
+}
+
+/// Exports the preferred sequencer of the genesis, if it is still registered.
+fn export_sequencer_registry<Da: DaSpec>(
+    template: &GenesisSections,
+    working_set: &mut WorkingSet<DefaultContext>,
+) -> anyhow::Result<SequencerConfig<DefaultContext, Da>> {
+    let mut genesis: SequencerConfig<DefaultContext, Da> =
+        template_section(template, "sequencer_registry")?;
+
+    let registered = SequencerRegistry::<DefaultContext, Da>::default()
+        .sequencer_address(genesis.seq_da_address.clone(), working_set)
+        .map_err(|e| anyhow!("Failed to query the sequencer registry: {}", e))?
+        .address;
+    let Some(seq_rollup_address) = registered else {
+        bail!(
+            "The genesis sequencer {} is no longer registered, the exported genesis would have no sequencer",
+            genesis.seq_da_address
+        );
+    };
+
+    genesis.seq_rollup_address = seq_rollup_address;
+    Ok(genesis)
+}
+
+/// Exports the balances of the genesis tokens.
+///
+/// The coins the sequencer locked are credited back to it, since the exported genesis locks
+/// them again. The fees held by the `tx_fees` module and paid to its fee recipient are exported
+/// as well, so the balances of every token add up to its supply.
+fn export_bank<Da: DaSpec>(
+    template: &GenesisSections,
+    account_addresses: &[Address],
+    sequencer_registry: &SequencerConfig<DefaultContext, Da>,
+    latest_state: &mut WorkingSet<DefaultContext>,
+    working_set: &mut WorkingSet<DefaultContext>,
+) -> anyhow::Result<BankConfig<DefaultContext>> {
+    let mut genesis: BankConfig<DefaultContext> = template_section(template, "bank")?;
+    let indexer = Indexer::<DefaultContext>::default();
+    let bank = Bank::<DefaultContext>::default();
+
+    let genesis_tokens: Vec<Address> = genesis
+        .tokens
+        .iter()
+        .map(|token| {
+            sov_bank::get_genesis_token_address::<DefaultContext>(&token.token_name, token.salt)
+        })
+        .collect();
+    let created: Vec<Address> =
+        read_all(|offset, limit| indexer.tokens(offset, limit, latest_state))?
+            .into_iter()
+            .filter(|token_address| !genesis_tokens.contains(token_address))
+            .collect();
+    if let Some(token_address) = created.first() {
+        bail!(
+            "{} tokens were created after genesis, such as {token_address}, and cannot be \
+             recreated at the same address by the genesis",
+            created.len()
+        );
+    }
+
+    let tx_fees: TxFeesConfig<DefaultContext> = template_section(template, "tx_fees")?;
+    let fee_holders = [
+        *TxFees::<DefaultContext>::default().address(),
+        tx_fees.fee_recipient,
+    ];
+
+    for (token, token_address) in genesis.tokens.iter_mut().zip(genesis_tokens) {
+        let mut candidates: Vec<Address> = token
+            .address_and_balances
+            .iter()
+            .map(|(address, _)| *address)
+            .collect();
+        candidates.extend(read_all(|offset, limit| {
+            indexer.token_holders_of(token_address, offset, limit, latest_state)
+        })?);
+        candidates.extend_from_slice(account_addresses);
+        candidates.extend_from_slice(&token.authorized_minters);
+        candidates.extend_from_slice(&fee_holders);
+        candidates.push(sequencer_registry.seq_rollup_address);
+
+        let mut balances: Vec<(Address, u64)> = Vec::new();
+        for holder in candidates {
+            if balances.iter().any(|(address, _)| *address == holder) {
+                continue;
+            }
+            let mut balance = bank
+                .balance_of(holder, token_address, working_set)
+                .map_err(|e| anyhow!("Failed to query the bank: {}", e))?
+                .amount
+                .unwrap_or_default();
+            if holder == sequencer_registry.seq_rollup_address
+                && sequencer_registry.coins_to_lock.token_address == token_address
+            {
+                balance += sequencer_registry.coins_to_lock.amount;
+            }
+            balances.push((holder, balance));
+        }
+        balances.retain(|(_, balance)| *balance > 0);
+
+        let exported: u64 = balances.iter().map(|(_, balance)| balance).sum();
+        let supply = bank
+            .supply_of(token_address, working_set)
+            .map_err(|e| anyhow!("Failed to query the bank: {}", e))?
+            .amount
+            .unwrap_or_default();
+        if exported != supply {
+            bail!(
+                "Found {exported} of token `{}`, out of a supply of {supply}: the other holders \
+                 are not indexed, such as the sequencers registered after genesis and paid from \
+                 the reward pool",
+                token.token_name
+            );
+        }
+
+        token.address_and_balances = balances;
+    }
+    Ok(genesis)
+}
+
+/// Reads every page of an `indexer` list.
+fn read_all<T>(
+    mut page: impl FnMut(u64, u64) -> jsonrpsee::core::RpcResult<indexer::Page<T>>,
+) -> anyhow::Result<Vec<T>> {
+    let mut items = Vec::new();
+    loop {
+        let next = page(items.len() as u64, indexer::MAX_PAGE_SIZE)
+            .map_err(|e| anyhow!("Failed to query the indexer: {}", e))?;
+        let read = next.items.len();
+        items.extend(next.items);
+        if read == 0 || items.len() as u64 >= next.total {
+            return Ok(items);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+#![deny(missing_docs)]
+//! Generation and hashing of the rollup genesis.
+//!
+//! The [`GenesisBuilder`] derives the token addresses of the genesis from the token names, so
+//! the module configs always agree with each other. The [`genesis_hash`] is a hash of the genesis
+//! configuration, not of the genesis state: it does not depend on the layout of the genesis (a
+//! directory of JSON files, or a single JSON or TOML file), so the validators of a network can
+//! compare it to check that they start from the same configuration. Configurations listing the
+//! same entries in another order have different hashes, even though they lead to the same state.
+
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use chain_history::ChainHistoryConfig;
+use sequencer_rewards::SequencerRewardsConfig;
+use serde::Serialize;
+use sov_accounts::AccountConfig;
+use sov_bank::{BankConfig, Coins, TokenConfig};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::digest::Digest;
+use sov_modules_api::{DaSpec, PublicKey, Spec};
+use sov_sequencer_registry::SequencerConfig;
+use stf_starter::genesis_config::GenesisPaths;
+use tx_fees::TxFeesConfig;
+
+type Address = <DefaultContext as Spec>::Address;
+type DefaultPublicKey = <DefaultContext as Spec>::PublicKey;
+
+/// The sections of a genesis, named after the `Runtime` fields, plus the kernel `chain_state`.
+pub const GENESIS_SECTIONS: &[&str] = &[
+    "accounts",
+    "bank",
+    "sequencer_registry",
+    "sequencer_rewards",
+    "tx_fees",
+    "chain_history",
+    #[cfg(feature = "experimental")]
+    "evm",
+    "chain_state",
+];
+
+/// The sections of a genesis, keyed by name.
+pub type GenesisSections = serde_json::Map<String, serde_json::Value>;
+
+/// Builds a genesis with a gas token, used for the fees and the sequencer rewards, a token the
+/// sequencer locks to register, and a single preferred sequencer.
+pub struct GenesisBuilder<Da: DaSpec> {
+    /// The rollup address of the sequencer. It receives the fees and the rewards, and mints both
+    /// tokens, so its public key must be one of the `accounts`.
+    pub sequencer_rollup_address: Address,
+    /// The DA address the sequencer submits batches from.
+    pub sequencer_da_address: Da::Address,
+    /// The public keys of the accounts registered at genesis.
+    pub accounts: Vec<DefaultPublicKey>,
+    /// The genesis balances of the gas token, besides the reward pool.
+    pub balances: Vec<(Address, u64)>,
+    /// The name of the gas token.
+    pub token_name: String,
+    /// The salt of the gas token.
+    pub token_salt: u64,
+    /// The name of the token the sequencer locks to register.
+    pub lock_token_name: String,
+    /// The salt of the token the sequencer locks.
+    pub lock_token_salt: u64,
+    /// The genesis balance of the sequencer in the token it locks.
+    pub lock_token_balance: u64,
+    /// The amount of tokens the sequencer locks to register.
+    pub coins_to_lock: u64,
+    /// The account the sequencer rewards are paid from.
+    pub reward_pool_address: Address,
+    /// The genesis balance of the reward pool in the gas token.
+    pub reward_pool_balance: u64,
+    /// The price of a unit of gas.
+    pub gas_price: u64,
+    /// The number of slots kept by the `chain_history` module.
+    pub window_size: u64,
+}
+
+impl<Da: DaSpec> GenesisBuilder<Da> {
+    /// The address of the gas token, derived from its name and salt.
+    pub fn token_address(&self) -> Address {
+        sov_bank::get_genesis_token_address::<DefaultContext>(&self.token_name, self.token_salt)
+    }
+
+    /// The address of the token the sequencer locks, derived from its name and salt.
+    pub fn lock_token_address(&self) -> Address {
+        sov_bank::get_genesis_token_address::<DefaultContext>(
+            &self.lock_token_name,
+            self.lock_token_salt,
+        )
+    }
+
+    /// Builds the genesis sections, taking the sections the builder does not generate (such as
+    /// the kernel `chain_state`) from the `template`.
+    pub fn build(&self, template: &GenesisSections) -> anyhow::Result<GenesisSections> {
+        let token_address = self.token_address();
+
+        let accounts = AccountConfig::<DefaultContext> {
+            pub_keys: self.accounts.clone(),
+        };
+        let mut balances = self.balances.clone();
+        balances.push((self.reward_pool_address, self.reward_pool_balance));
+        let bank = BankConfig::<DefaultContext> {
+            tokens: vec![
+                TokenConfig {
+                    token_name: self.lock_token_name.clone(),
+                    address_and_balances: vec![(
+                        self.sequencer_rollup_address,
+                        self.lock_token_balance,
+                    )],
+                    authorized_minters: vec![self.sequencer_rollup_address],
+                    salt: self.lock_token_salt,
+                },
+                TokenConfig {
+                    token_name: self.token_name.clone(),
+                    address_and_balances: balances,
+                    authorized_minters: vec![self.sequencer_rollup_address],
+                    salt: self.token_salt,
+                },
+            ],
+        };
+        let sequencer_registry = SequencerConfig::<DefaultContext, Da> {
+            seq_rollup_address: self.sequencer_rollup_address,
+            seq_da_address: self.sequencer_da_address.clone(),
+            coins_to_lock: Coins {
+                amount: self.coins_to_lock,
+                token_address: self.lock_token_address(),
+            },
+            is_preferred_sequencer: true,
+        };
+        let sequencer_rewards = SequencerRewardsConfig::<DefaultContext, Da> {
+            reward_token_address: token_address,
+            reward_pool_address: self.reward_pool_address,
+            // Filled from the `sequencer_registry` section when the genesis is read
+            payout_addresses: Vec::new(),
+        };
+        let tx_fees = TxFeesConfig::<DefaultContext> {
+            gas_token_address: token_address,
+            gas_price: self.gas_price,
+            fee_recipient: self.sequencer_rollup_address,
+        };
+        let chain_history = ChainHistoryConfig {
+            window_size: self.window_size,
+        };
+
+        let mut sections = template.clone();
+        insert_section(&mut sections, "accounts", &accounts)?;
+        insert_section(&mut sections, "bank", &bank)?;
+        insert_section(&mut sections, "sequencer_registry", &sequencer_registry)?;
+        insert_section(&mut sections, "sequencer_rewards", &sequencer_rewards)?;
+        insert_section(&mut sections, "tx_fees", &tx_fees)?;
+        insert_section(&mut sections, "chain_history", &chain_history)?;
+        Ok(sections)
+    }
+}
+
+fn insert_section(
+    sections: &mut GenesisSections,
+    name: &str,
+    config: &impl Serialize,
+) -> anyhow::Result<()> {
+    let value = serde_json::to_value(config)
+        .with_context(|| format!("Failed to serialize the `{name}` genesis"))?;
+    sections.insert(name.to_owned(), value);
+    Ok(())
+}
+
+/// Reads the sections of a genesis. The `chain_state` section is read from
+/// `kernel_genesis_path` if one is given, which is required for a genesis directory.
+pub fn read_genesis_sections(
+    genesis_paths: &GenesisPaths,
+    kernel_genesis_path: Option<&Path>,
+) -> anyhow::Result<GenesisSections> {
+    let mut sections = match genesis_paths {
+        GenesisPaths::Modules(paths) => {
+            let mut sections = GenesisSections::new();
+            for (section, path) in [
+                ("accounts", &paths.accounts_genesis_path),
+                ("bank", &paths.bank_genesis_path),
+                ("sequencer_registry", &paths.sequencer_genesis_path),
+                ("sequencer_rewards", &paths.sequencer_rewards_genesis_path),
+                ("tx_fees", &paths.tx_fees_genesis_path),
+                ("chain_history", &paths.chain_history_genesis_path),
+                #[cfg(feature = "experimental")]
+                ("evm", &paths.evm_genesis_path),
+            ] {
+                sections.insert(section.to_owned(), read_genesis_value(path)?);
+            }
+            sections
+        }
+        GenesisPaths::File(path) => {
+            let serde_json::Value::Object(sections) = read_genesis_value(path)? else {
+                bail!("The genesis file {} is not a table", path.display());
+            };
+            sections
+        }
+    };
+
+    if let Some(kernel_genesis_path) = kernel_genesis_path {
+        sections.insert(
+            "chain_state".to_owned(),
+            read_genesis_value(kernel_genesis_path)?,
+        );
+    }
+
+    // Sections unknown to the runtime do not change the genesis state
+    sections.retain(|section, _| GENESIS_SECTIONS.contains(&section.as_str()));
+    if let Some(missing) = GENESIS_SECTIONS
+        .iter()
+        .find(|section| !sections.contains_key(**section))
+    {
+        bail!("The genesis has no `{missing}` section");
+    }
+    Ok(sections)
+}
+
+fn read_genesis_value(path: &Path) -> anyhow::Result<serde_json::Value> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        sov_stf_runner::from_toml_path(path)
+    } else {
+        sov_stf_runner::read_json_file(path)
+    }
+    .with_context(|| format!("Failed to read the genesis file {}", path.display()))
+}
+
+/// Writes the genesis sections to `output`: a single file if it ends in `.json` or `.toml`,
+/// and a directory with a JSON file per section otherwise.
+pub fn write_genesis_sections(sections: &GenesisSections, output: &Path) -> anyhow::Result<()> {
+    let write = |path: &Path, contents: String| {
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write the genesis file {}", path.display()))
+    };
+
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => write(output, serde_json::to_string_pretty(sections)?),
+        Some("toml") => write(output, toml::to_string_pretty(sections)?),
+        _ => {
+            std::fs::create_dir_all(output).with_context(|| {
+                format!(
+                    "Failed to create the genesis directory {}",
+                    output.display()
+                )
+            })?;
+            for (section, value) in sections {
+                write(
+                    &output.join(format!("{section}.json")),
+                    serde_json::to_string_pretty(value)?,
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Hashes the configuration in the genesis sections. Object keys are sorted, so the hash only
+/// depends on the contents of the configuration, but the order of the arrays is kept.
+pub fn genesis_hash(sections: &GenesisSections) -> [u8; 32] {
+    let canonical = canonicalize(&serde_json::Value::Object(sections.clone()));
+    <DefaultContext as Spec>::Hasher::digest(canonical.to_string()).into()
+}
+
+fn canonicalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), canonicalize(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.iter().map(canonicalize).collect())
+        }
+        value => value.clone(),
+    }
+}
+
+/// Parses a value the way it is written in the genesis files.
+pub fn parse_genesis_value<T: serde::de::DeserializeOwned>(value: &str) -> anyhow::Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_owned()))
+        .with_context(|| format!("Invalid value {value:?}"))
+}
+
+/// Parses an `<address>=<amount>` genesis balance.
+pub fn parse_balance(balance: &str) -> anyhow::Result<(Address, u64)> {
+    let Some((address, amount)) = balance.split_once('=') else {
+        bail!("Invalid balance {balance:?}, expected <address>=<amount>");
+    };
+    let amount = amount
+        .parse()
+        .with_context(|| format!("Invalid amount in balance {balance:?}"))?;
+    Ok((parse_genesis_value(address)?, amount))
+}
+
+/// The addresses of the accounts registered in a genesis, derived from their public keys.
+pub fn account_addresses(accounts: &[DefaultPublicKey]) -> Vec<Address> {
+    accounts
+        .iter()
+        .map(|pub_key| pub_key.to_address::<Address>())
+        .collect()
+}
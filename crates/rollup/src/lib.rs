@@ -1,5 +1,24 @@
+pub mod consistency;
+pub mod export;
+pub mod genesis;
+pub mod metrics;
+pub mod proofs;
+pub mod prover;
+pub mod pruning;
+pub mod sequencer;
+pub mod shutdown;
+pub mod snapshot;
+pub mod starter_rollup;
+pub mod status;
+pub mod telemetry;
+
 #[cfg(feature = "mock_da")]
 pub mod mock_rollup;
+#[cfg(feature = "mock_da")]
+pub mod persistent_mock_da;
 
 #[cfg(feature = "celestia_da")]
 pub mod celestia_rollup;
+
+#[cfg(feature = "experimental")]
+pub mod eth;
@@ -0,0 +1,364 @@
+#![deny(missing_docs)]
+//! Prometheus metrics of the node, served on the `/metrics` endpoint configured by the
+//! `[metrics]` section of the rollup config.
+//!
+//! The slot metrics are collected from the ledger once a slot has been processed: the batch
+//! receipts are the outcomes the `Runtime` blob hooks returned, so they tell which sequencers
+//! were rewarded or slashed, and the transaction receipts tell which transactions reverted.
+//! The RPC metrics are recorded by the RPC server of the node, started by [`start_rpc_server`].
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context as _};
+use chain_history::ChainHistory;
+use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, Params, TransportProtocol};
+use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::RpcModule;
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use serde::{Deserialize, Serialize};
+use sov_db::ledger_db::LedgerDB;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{BlobReaderTrait, DaSpec, Spec, WorkingSet};
+use sov_modules_stf_blueprint::{SequencerOutcome, TxEffect};
+use sov_rollup_interface::rpc::{ItemOrHash, LedgerRpcProvider, QueryMode, SlotResponse};
+use sov_stf_runner::{from_toml_path, RpcConfig};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{oneshot, Notify};
+use tracing::{info, warn};
+
+/// The `[metrics]` section of the rollup config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// The host to serve the metrics on.
+    pub bind_host: String,
+    /// The port to serve the metrics on.
+    pub bind_port: u16,
+}
+
+#[derive(Deserialize)]
+struct MetricsSection {
+    #[serde(default)]
+    metrics: Option<MetricsConfig>,
+}
+
+impl MetricsConfig {
+    /// Reads the `[metrics]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: MetricsSection =
+            from_toml_path(path).context("Failed to read the `[metrics]` section")?;
+        Ok(section.metrics)
+    }
+
+    /// Starts serving the metrics. Must be called from the tokio runtime of the node.
+    pub fn install(&self) -> anyhow::Result<()> {
+        let bind_host: IpAddr = self
+            .bind_host
+            .parse()
+            .with_context(|| format!("Invalid metrics bind host {:?}", self.bind_host))?;
+        let address = SocketAddr::new(bind_host, self.bind_port);
+        PrometheusBuilder::new()
+            .with_http_listener(address)
+            .install()
+            .context("Failed to start the metrics endpoint")?;
+        describe_metrics();
+        info!("Serving metrics on http://{address}/metrics");
+        Ok(())
+    }
+}
+
+fn describe_metrics() {
+    describe_counter!(
+        "rollup_slots_processed_total",
+        "The slots processed by the node"
+    );
+    describe_gauge!(
+        "rollup_height",
+        "The rollup height of the last processed slot"
+    );
+    describe_gauge!(
+        "rollup_da_height",
+        "The DA height of the last processed slot"
+    );
+    describe_histogram!("rollup_batches_per_slot", "The number of batches in a slot");
+    describe_counter!(
+        "rollup_batches_total",
+        "The processed batches, by outcome: rewarded, slashed or ignored"
+    );
+    describe_counter!(
+        "rollup_sequencer_rewards_total",
+        "The tokens rewarded to the sequencers"
+    );
+    describe_counter!(
+        "rollup_txs_total",
+        "The transactions in the processed batches, by outcome: applied or reverted"
+    );
+    describe_gauge!(
+        "rollup_prover_queue_depth",
+        "The processed slots the prover has not started to prove"
+    );
+    describe_counter!("rpc_requests_total", "The RPC calls, by method");
+    describe_counter!(
+        "rpc_request_errors_total",
+        "The failed RPC calls, by method"
+    );
+    describe_histogram!(
+        "rpc_request_duration_seconds",
+        "The latency of the RPC requests, by method"
+    );
+}
+
+/// The slots waiting for the prover, and those handed to it and not proven yet. Slots are only
+/// queued when the prover runs.
+#[derive(Clone, Default)]
+pub struct ProverQueue {
+    enabled: Arc<AtomicBool>,
+    depth: Arc<AtomicU64>,
+    submitted: Arc<AtomicU64>,
+    proved: Arc<Notify>,
+}
+
+impl ProverQueue {
+    /// Starts counting the slots waiting for the prover.
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    fn push(&self) {
+        if self.enabled.load(Ordering::Relaxed) {
+            let depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+            gauge!("rollup_prover_queue_depth", depth as f64);
+        }
+    }
+
+    /// Records that a slot was handed to the prover, until the returned guard is dropped once
+    /// the slot is proven.
+    pub(crate) fn submit(&self) -> SubmittedSlot {
+        self.submitted.fetch_add(1, Ordering::SeqCst);
+        SubmittedSlot {
+            queue: self.clone(),
+        }
+    }
+
+    /// Records that the prover started to prove a slot.
+    ///
+    /// Slots processed before the node started were never queued, so the depth does not go
+    /// below 0 when the prover catches up with them.
+    pub(crate) fn start_proving(&self) {
+        let previous = self
+            .depth
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |depth| {
+                Some(depth.saturating_sub(1))
+            })
+            .expect("The update always succeeds");
+        gauge!(
+            "rollup_prover_queue_depth",
+            previous.saturating_sub(1) as f64
+        );
+    }
+
+    /// The number of slots waiting for the prover.
+    pub fn depth(&self) -> u64 {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Completes once the prover has proven every slot handed to it, including the slots waiting
+    /// for a free worker.
+    pub async fn proofs_finished(&self) {
+        loop {
+            // Created before the check, so a proof finishing in between still wakes it up
+            let proved = self.proved.notified();
+            if self.submitted.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            proved.await;
+        }
+    }
+}
+
+/// A slot handed to the prover, see [`ProverQueue::submit`].
+pub(crate) struct SubmittedSlot {
+    queue: ProverQueue,
+}
+
+impl Drop for SubmittedSlot {
+    fn drop(&mut self) {
+        self.queue.submitted.fetch_sub(1, Ordering::SeqCst);
+        self.queue.proved.notify_waiters();
+    }
+}
+
+type BatchReceipt<Da> =
+    SequencerOutcome<<<Da as DaSpec>::BlobTransaction as BlobReaderTrait>::Address>;
+
+/// Records the metrics of every slot the node processes, until the ledger is closed.
+pub async fn collect_slot_metrics<Da: DaSpec>(
+    ledger_db: LedgerDB,
+    storage: <DefaultContext as Spec>::Storage,
+    prover_queue: ProverQueue,
+) {
+    let mut slots = ledger_db.subscribe_slots();
+    loop {
+        let slot_number = match slots.recv().await {
+            Ok(slot_number) => slot_number,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("The metrics of {} slots were not recorded", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        if let Err(e) = record_slot::<Da>(&ledger_db, &storage, slot_number, &prover_queue) {
+            warn!(
+                "Failed to record the metrics of slot {}: {:?}",
+                slot_number, e
+            );
+        }
+    }
+}
+
+fn record_slot<Da: DaSpec>(
+    ledger_db: &LedgerDB,
+    storage: &<DefaultContext as Spec>::Storage,
+    slot_number: u64,
+    prover_queue: &ProverQueue,
+) -> anyhow::Result<()> {
+    let slot: SlotResponse<BatchReceipt<Da>, TxEffect> = ledger_db
+        .get_slot_by_number(slot_number, QueryMode::Full)?
+        .ok_or_else(|| anyhow!("The slot is not in the ledger"))?;
+
+    counter!("rollup_slots_processed_total", 1);
+    prover_queue.push();
+
+    let mut working_set = WorkingSet::new(storage.clone());
+    let chain_history = ChainHistory::<DefaultContext>::default();
+    let height = chain_history.get_current_height(&mut working_set);
+    gauge!("rollup_height", height as f64);
+    if let Some(slot) = chain_history.get_slot(height, &mut working_set) {
+        gauge!("rollup_da_height", slot.da_height as f64);
+    }
+
+    let batches = slot.batches.unwrap_or_default();
+    histogram!("rollup_batches_per_slot", batches.len() as f64);
+    for batch in batches {
+        let ItemOrHash::Full(batch) = batch else {
+            continue;
+        };
+        let outcome = match batch.custom_receipt {
+            SequencerOutcome::Rewarded(reward) => {
+                counter!("rollup_sequencer_rewards_total", reward);
+                "rewarded"
+            }
+            SequencerOutcome::Slashed { .. } => "slashed",
+            SequencerOutcome::Ignored => "ignored",
+        };
+        counter!("rollup_batches_total", 1, "outcome" => outcome);
+
+        for tx in batch.txs.unwrap_or_default() {
+            let ItemOrHash::Full(tx) = tx else {
+                continue;
+            };
+            let outcome = match tx.custom_receipt {
+                TxEffect::Successful => "applied",
+                TxEffect::Reverted => "reverted",
+            };
+            counter!("rollup_txs_total", 1, "outcome" => outcome);
+        }
+    }
+    Ok(())
+}
+
+/// Records the calls, failures and latency of the RPC methods, by method.
+///
+/// The calls of methods that are not served are recorded under the `unknown` method, so clients
+/// cannot add labels to the metrics.
+#[derive(Clone)]
+pub struct RpcMetrics {
+    method_names: Arc<HashSet<&'static str>>,
+}
+
+impl RpcMetrics {
+    /// Records the calls of `rpc_methods`.
+    pub fn new(rpc_methods: &RpcModule<()>) -> Self {
+        Self {
+            method_names: Arc::new(rpc_methods.method_names().collect()),
+        }
+    }
+
+    fn method_label(&self, method_name: &str) -> &'static str {
+        self.method_names
+            .get(method_name)
+            .copied()
+            .unwrap_or("unknown")
+    }
+}
+
+impl Logger for RpcMetrics {
+    type Instant = Instant;
+
+    fn on_connect(&self, _: SocketAddr, _: &HttpRequest, _: TransportProtocol) {}
+
+    fn on_request(&self, _: TransportProtocol) -> Self::Instant {
+        Instant::now()
+    }
+
+    fn on_call(&self, method_name: &str, _: Params, _: MethodKind, _: TransportProtocol) {
+        counter!("rpc_requests_total", 1, "method" => self.method_label(method_name));
+    }
+
+    fn on_result(
+        &self,
+        method_name: &str,
+        success: bool,
+        _: Option<i32>,
+        started_at: Self::Instant,
+        _: TransportProtocol,
+    ) {
+        let method = self.method_label(method_name);
+        histogram!(
+            "rpc_request_duration_seconds",
+            started_at.elapsed().as_secs_f64(),
+            "method" => method
+        );
+        if !success {
+            counter!("rpc_request_errors_total", 1, "method" => method);
+        }
+    }
+
+    fn on_response(&self, _: &str, _: Self::Instant, _: TransportProtocol) {}
+
+    fn on_disconnect(&self, _: SocketAddr, _: TransportProtocol) {}
+}
+
+/// Serves `rpc_methods` on the RPC address of the runner, recording their [`RpcMetrics`], and
+/// reports the bound address to `rpc_reporting_channel`.
+///
+/// The server stops when the returned handle is stopped or dropped.
+pub async fn start_rpc_server(
+    rpc_methods: RpcModule<()>,
+    rpc_config: &RpcConfig,
+    rpc_reporting_channel: Option<oneshot::Sender<SocketAddr>>,
+) -> anyhow::Result<ServerHandle> {
+    let bind_host: IpAddr = rpc_config
+        .bind_host
+        .parse()
+        .with_context(|| format!("Invalid RPC bind_host {:?}", rpc_config.bind_host))?;
+    let server = ServerBuilder::default()
+        .set_logger(RpcMetrics::new(&rpc_methods))
+        .build(SocketAddr::new(bind_host, rpc_config.bind_port))
+        .await
+        .context("Failed to start the RPC server")?;
+
+    let address = server.local_addr()?;
+    if let Some(channel) = rpc_reporting_channel {
+        // The receiver may not care about the address
+        let _ = channel.send(address);
+    }
+    info!("Serving the RPC methods on {address}");
+    Ok(server.start(rpc_methods))
+}
@@ -1,137 +1,113 @@
 #![deny(missing_docs)]
-//! StarterRollup provides a minimal self-contained rollup implementation
+//! The mock DA adapter of the [`StarterRollup`].
+
+use std::path::PathBuf;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use sov_db::ledger_db::LedgerDB;
-use sov_mock_da::{MockDaConfig, MockDaService, MockDaSpec};
-use sov_modules_api::default_context::{DefaultContext, ZkDefaultContext};
-use sov_modules_api::Address;
-use sov_modules_api::Spec;
-use sov_modules_rollup_blueprint::RollupBlueprint;
-use sov_modules_stf_blueprint::kernels::basic::BasicKernel;
-use sov_modules_stf_blueprint::StfBlueprint;
-use sov_prover_storage_manager::ProverStorageManager;
-use sov_risc0_adapter::host::Risc0Host;
-use sov_rollup_interface::zk::ZkvmHost;
-use sov_state::config::Config as StorageConfig;
-use sov_state::Storage;
-use sov_state::{DefaultStorageSpec, ZkStorage};
-use sov_stf_runner::ParallelProverService;
+use serde::{Deserialize, Serialize};
+use sov_mock_da::{MockAddress, MockDaSpec, MockDaVerifier};
 use sov_stf_runner::RollupConfig;
-use sov_stf_runner::RollupProverConfig;
-use stf_starter::Runtime;
-
-/// Rollup with [`MockDaService`].
-pub struct MockRollup {}
-
-/// This is the place, where all the rollup components come together and
-/// they can be easily swapped with alternative implementations as needed.
-#[async_trait]
-impl RollupBlueprint for MockRollup {
-    /// This component defines the Data Availability layer.
-    type DaService = MockDaService;
-    type DaSpec = MockDaSpec;
-    type DaConfig = MockDaConfig;
 
-    /// The concrete ZkVm used in the rollup.
-    type Vm = Risc0Host<'static>;
-
-    /// Context for the Zero Knowledge environment.
-    type ZkContext = ZkDefaultContext;
-    /// Context for the ZNative environment.
-    type NativeContext = DefaultContext;
-
-    /// Manager for the native storage lifecycle.
-    type StorageManager = ProverStorageManager<MockDaSpec, DefaultStorageSpec>;
-
-    /// Runtime for the Zero Knowledge environment.
-    type ZkRuntime = Runtime<Self::ZkContext, Self::DaSpec>;
-    /// Runtime for the Native environment.
-    type NativeRuntime = Runtime<Self::NativeContext, Self::DaSpec>;
-
-    /// Kernels.
-    type NativeKernel = BasicKernel<Self::NativeContext, Self::DaSpec>;
-    type ZkKernel = BasicKernel<Self::ZkContext, Self::DaSpec>;
+use crate::persistent_mock_da::{BlockProduction, PersistentMockDaService, Reorgs};
+use crate::starter_rollup::{DaAdapter, StarterRollup};
+
+/// Rollup with [`PersistentMockDaService`].
+pub type MockRollup = StarterRollup<MockDa>;
+
+/// The `[da]` section of the rollup config of the mock DA layer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockDaSettings {
+    /// The address the blobs are submitted from.
+    pub sender_address: MockAddress,
+    /// The directory the mock DA layer is persisted to. Nodes configured with the same directory
+    /// share the same DA layer. Without it, the DA layer is kept in memory and lost on restart.
+    #[serde(default)]
+    pub db_path: Option<PathBuf>,
+    /// The minimum time between two blocks, in milliseconds. Without it, a block is produced as
+    /// soon as a blob is submitted.
+    #[serde(default)]
+    pub block_time_ms: Option<u64>,
+    /// Whether to produce empty blocks when no blob is submitted for a whole `block_time_ms`.
+    /// Only one of the nodes sharing a `db_path` should produce them.
+    #[serde(default)]
+    pub produce_empty_blocks: bool,
+    /// The maximum size of a blob, in bytes. Larger blobs are rejected.
+    #[serde(default)]
+    pub max_blob_size: Option<usize>,
+    /// The number of blocks produced on top of a block before it is final. The rollup only
+    /// processes final blocks.
+    #[serde(default)]
+    pub blocks_to_finality: u32,
+    /// The number of blocks that are not final yet replaced by a reorg, at most
+    /// `blocks_to_finality`. Without it, there are no reorgs.
+    #[serde(default)]
+    pub reorg_depth: Option<u32>,
+    /// The number of blocks between two reorgs.
+    #[serde(default = "default_reorg_interval")]
+    pub reorg_interval: u64,
+}
 
-    /// Prover service.
-    type ProverService = ParallelProverService<
-        <<Self::NativeContext as Spec>::Storage as Storage>::Root,
-        <<Self::NativeContext as Spec>::Storage as Storage>::Witness,
-        Self::DaService,
-        Self::Vm,
-        StfBlueprint<
-            Self::ZkContext,
-            Self::DaSpec,
-            <Self::Vm as ZkvmHost>::Guest,
-            Self::ZkRuntime,
-            Self::ZkKernel,
-        >,
-    >;
+fn default_reorg_interval() -> u64 {
+    10
+}
 
-    /// This function generates RPC methods for the rollup, allowing for extension with custom endpoints.
-    fn create_rpc_methods(
-        &self,
-        storage: &<Self::NativeContext as Spec>::Storage,
-        ledger_db: &LedgerDB,
-        da_service: &Self::DaService,
-    ) -> Result<jsonrpsee::RpcModule<()>, anyhow::Error> {
-        // TODO set the sequencer address
-        let sequencer = Address::new([0; 32]);
+impl MockDaSettings {
+    /// How the mock DA layer produces blocks.
+    pub fn block_production(&self) -> BlockProduction {
+        BlockProduction {
+            block_time: self.block_time_ms.map(Duration::from_millis),
+            produce_empty_blocks: self.produce_empty_blocks,
+            max_blob_size: self.max_blob_size,
+            blocks_to_finality: self.blocks_to_finality,
+            reorgs: self.reorg_depth.map(|depth| Reorgs {
+                depth,
+                interval: self.reorg_interval,
+            }),
+        }
+    }
+}
 
-        #[allow(unused_mut)]
-        let mut rpc_methods = sov_modules_rollup_blueprint::register_rpc::<
-            Self::NativeRuntime,
-            Self::NativeContext,
-            Self::DaService,
-        >(storage, ledger_db, da_service, sequencer)?;
+/// The in-process mock DA layer, for local development and tests.
+pub struct MockDa;
 
-        #[cfg(feature = "experimental")]
-        crate::eth::register_ethereum::<Self::DaService>(
-            da_service.clone(),
-            storage.clone(),
-            &mut rpc_methods,
-        )?;
+#[async_trait]
+impl DaAdapter for MockDa {
+    type DaService = PersistentMockDaService;
+    type DaSpec = MockDaSpec;
+    type DaConfig = MockDaSettings;
 
-        Ok(rpc_methods)
-    }
+    const GUEST_ELF: &'static [u8] = risc0_starter::MOCK_DA_ELF;
+    const GUEST_ID: [u32; 8] = risc0_starter::MOCK_DA_ID;
 
     async fn create_da_service(
-        &self,
         rollup_config: &RollupConfig<Self::DaConfig>,
-    ) -> Self::DaService {
-        MockDaService::new(rollup_config.da.sender_address)
+    ) -> anyhow::Result<Self::DaService> {
+        let sender_address = rollup_config.da.sender_address;
+        let block_production = rollup_config.da.block_production();
+        match &rollup_config.da.db_path {
+            Some(db_path) => {
+                PersistentMockDaService::open(sender_address, db_path, block_production).await
+            }
+            None => PersistentMockDaService::in_memory(sender_address, block_production),
+        }
     }
 
-    async fn create_prover_service(
-        &self,
-        prover_config: RollupProverConfig,
-        rollup_config: &RollupConfig<Self::DaConfig>,
-        _da_service: &Self::DaService,
-    ) -> Self::ProverService {
-        let vm = Risc0Host::new(risc0_starter::MOCK_DA_ELF);
-        let zk_stf = StfBlueprint::new();
-        let zk_storage = ZkStorage::new();
-        let da_verifier = Default::default();
-
-        ParallelProverService::new_with_default_workers(
-            vm,
-            zk_stf,
-            da_verifier,
-            prover_config,
-            zk_storage,
-            rollup_config.prover_service,
-        )
+    fn create_da_verifier() -> MockDaVerifier {
+        Default::default()
     }
 
-    fn create_storage_manager(
-        &self,
-        rollup_config: &RollupConfig<Self::DaConfig>,
-    ) -> anyhow::Result<Self::StorageManager> {
-        let storage_config = StorageConfig {
-            path: rollup_config.storage.path.clone(),
-        };
-        ProverStorageManager::new(storage_config)
+    async fn check_sequencer(
+        da_config: &Self::DaConfig,
+        sequencer_da_address: &MockAddress,
+    ) -> anyhow::Result<()> {
+        if sequencer_da_address != &da_config.sender_address {
+            anyhow::bail!(
+                "Sequencer DA address {} does not match the mock DA sender address {}",
+                sequencer_da_address,
+                da_config.sender_address
+            );
+        }
+        Ok(())
     }
 }
-
-impl sov_modules_rollup_blueprint::WalletBlueprint for MockRollup {}
@@ -0,0 +1,709 @@
+#![deny(missing_docs)]
+//! The mock DA layer of the [`MockRollup`](crate::mock_rollup::MockRollup), with configurable
+//! block production, optionally persisted to a local directory.
+//!
+//! The blobs and aggregated proofs submitted by the node are appended to a log, and a block
+//! producer applies them to an in-memory chain of [`MockBlock`]s, one block per submission. A
+//! node submitting a blob waits until its block has been produced. When a block time is
+//! configured, the producer waits for it between two blocks, and can fill the gaps with empty
+//! blocks, which have no blob.
+//!
+//! A log persisted to a local directory survives restarts and can be shared by the nodes running
+//! on the same machine. Every node applies the whole log in order, so all the nodes see the same
+//! blocks at the same heights. The submissions are logged with their time, and the blobs with the
+//! `sender_address` of the node that submitted them, so replaying the log produces the same
+//! blocks. Only one of the nodes sharing a log should produce empty blocks.
+//!
+//! Blocks are final once `blocks_to_finality` blocks were produced on top of them, and never
+//! change. With a reorg depth, every `reorg_interval` blocks the last `reorg_depth` blocks, which
+//! are not final yet, are replaced by blocks with the same blobs and different hashes. The reorgs
+//! are derived from the heights, so the nodes sharing a log see the same ones.
+//!
+//! The rollup cannot roll back the blocks it processed, so [`DaService::get_block_at`] only
+//! returns final blocks: the rollup processes a block `blocks_to_finality` blocks after it is
+//! produced, and never sees the blocks a reorg replaces. Its last blobs are only processed once
+//! enough blocks are produced on top of them, so a chain with `blocks_to_finality` should produce
+//! empty blocks. The head of the chain, and its reorgs, are visible through
+//! [`DaService::get_head_block_header`].
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, bail, Context as _};
+use async_trait::async_trait;
+use borsh::{BorshDeserialize, BorshSerialize};
+use futures::Stream;
+use sha2::{Digest, Sha256};
+use sov_mock_da::{
+    MockAddress, MockBlob, MockBlock, MockBlockHeader, MockDaSpec, MockDaVerifier, MockHash,
+    MockValidityCond,
+};
+use sov_rollup_interface::da::{DaSpec, Time};
+use sov_rollup_interface::services::da::DaService;
+use tokio::sync::{broadcast, watch, Notify};
+use tracing::{debug, warn};
+
+/// The file the submissions are appended to, in the DA directory.
+const LOG_FILE_NAME: &str = "mock_da.log";
+
+/// How often the log is checked for the submissions of the other nodes.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The finalized headers buffered for the subscribers.
+const FINALIZED_HEADERS_CAPACITY: usize = 100;
+
+/// How the mock DA layer produces blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockProduction {
+    /// The minimum time between two blocks. Without it, blocks are produced as soon as blobs are
+    /// submitted.
+    pub block_time: Option<Duration>,
+    /// Whether to produce an empty block when nothing was submitted for a whole `block_time`.
+    pub produce_empty_blocks: bool,
+    /// The maximum size of a rollup blob, in bytes.
+    pub max_blob_size: Option<usize>,
+    /// The number of blocks produced on top of a block before it is final.
+    pub blocks_to_finality: u32,
+    /// The reorgs of the blocks that are not final yet, if any.
+    pub reorgs: Option<Reorgs>,
+}
+
+/// The reorgs of a mock DA layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reorgs {
+    /// The number of blocks a reorg replaces, at most `blocks_to_finality`.
+    pub depth: u32,
+    /// The number of blocks between two reorgs.
+    pub interval: u64,
+}
+
+impl BlockProduction {
+    fn check(&self) -> anyhow::Result<()> {
+        if let Some(reorgs) = &self.reorgs {
+            if reorgs.depth > self.blocks_to_finality {
+                bail!(
+                    "The reorg depth {} exceeds blocks_to_finality {}: final blocks never change",
+                    reorgs.depth,
+                    self.blocks_to_finality
+                );
+            }
+            if reorgs.interval == 0 {
+                bail!("The reorg interval must be at least one block");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A submission to the mock DA layer, as stored in the log, with the time of its block in
+/// seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+enum MockDaRecord {
+    /// A rollup blob, and the DA address that submitted it.
+    Blob {
+        sender: MockAddress,
+        time: i64,
+        data: Vec<u8>,
+    },
+    /// An aggregated proof, and the node that submitted it.
+    Proof {
+        writer: u64,
+        time: i64,
+        data: Vec<u8>,
+    },
+    /// A block without any blob.
+    EmptyBlock { time: i64 },
+}
+
+/// The current time, in seconds since the Unix epoch.
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("The system time is after the Unix epoch")
+        .as_secs() as i64
+}
+
+/// The log of the submissions. Positions in the log are record counts in memory, and byte
+/// offsets in a file.
+enum MockDaLog {
+    Memory(Mutex<Vec<MockDaRecord>>),
+    File(PathBuf),
+}
+
+impl MockDaLog {
+    /// Appends a record, returning the position of its end.
+    fn append(&self, record: &MockDaRecord) -> anyhow::Result<u64> {
+        match self {
+            MockDaLog::Memory(records) => {
+                let mut records = records.lock().expect("Mock DA lock poisoned");
+                records.push(record.clone());
+                Ok(records.len() as u64)
+            }
+            MockDaLog::File(path) => {
+                let serialized = record.try_to_vec()?;
+                let mut entry = Vec::with_capacity(serialized.len() + 4);
+                entry.extend_from_slice(&(serialized.len() as u32).to_le_bytes());
+                entry.extend_from_slice(&serialized);
+
+                // A single write of an append-only file, so the records of the nodes do not
+                // interleave
+                let mut file = std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(path)
+                    .with_context(|| {
+                        format!("Failed to open the mock DA log {}", path.display())
+                    })?;
+                file.write_all(&entry)?;
+                Ok(file.stream_position()?)
+            }
+        }
+    }
+
+    /// Reads the records after `position`, with the positions of their ends.
+    fn read_from(&self, position: u64) -> anyhow::Result<Vec<(MockDaRecord, u64)>> {
+        match self {
+            MockDaLog::Memory(records) => {
+                let records = records.lock().expect("Mock DA lock poisoned");
+                Ok(records
+                    .iter()
+                    .enumerate()
+                    .skip(position as usize)
+                    .map(|(index, record)| (record.clone(), index as u64 + 1))
+                    .collect())
+            }
+            MockDaLog::File(path) => {
+                let mut file = std::fs::File::open(path)?;
+                file.seek(SeekFrom::Start(position))?;
+                let mut new_entries = Vec::new();
+                file.read_to_end(&mut new_entries)?;
+
+                let mut records = Vec::new();
+                let mut end = position;
+                let mut entries = new_entries.as_slice();
+                // A record that is still being written is read on the next call
+                while entries.len() >= 4 {
+                    let len = u32::from_le_bytes(entries[..4].try_into().expect("Slice of 4 bytes"))
+                        as usize;
+                    if entries.len() < 4 + len {
+                        break;
+                    }
+                    let record = MockDaRecord::try_from_slice(&entries[4..4 + len])
+                        .context("Corrupted mock DA log")?;
+                    entries = &entries[4 + len..];
+                    end += (4 + len) as u64;
+                    records.push((record, end));
+                }
+                Ok(records)
+            }
+        }
+    }
+}
+
+/// A block of the chain, with the senders and the data of its blobs.
+struct StoredBlock {
+    block: MockBlock,
+    blobs: Vec<(MockAddress, Vec<u8>)>,
+}
+
+#[derive(Default)]
+struct ChainState {
+    blocks: Vec<StoredBlock>,
+    /// The aggregated proofs, by DA height.
+    proofs: HashMap<u64, Vec<Vec<u8>>>,
+    /// The number of reorgs so far, part of the block hashes.
+    reorgs: u64,
+}
+
+/// The blocks produced from the log, starting at height 1.
+struct MockChain {
+    blocks_to_finality: u32,
+    reorgs: Option<Reorgs>,
+    state: Mutex<ChainState>,
+    /// The height of the head.
+    head: watch::Sender<u64>,
+    finalized_headers: broadcast::Sender<MockBlockHeader>,
+}
+
+impl MockChain {
+    fn new(production: &BlockProduction) -> Self {
+        Self {
+            blocks_to_finality: production.blocks_to_finality,
+            reorgs: production.reorgs,
+            state: Mutex::new(ChainState::default()),
+            head: watch::channel(0).0,
+            finalized_headers: broadcast::channel(FINALIZED_HEADERS_CAPACITY).0,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChainState> {
+        self.state.lock().expect("Mock DA lock poisoned")
+    }
+
+    /// Produces a block with the given time, blobs and aggregated proof, returning its height.
+    fn produce_block(
+        &self,
+        time: Time,
+        blobs: Vec<(MockAddress, Vec<u8>)>,
+        proof: Option<Vec<u8>>,
+    ) -> u64 {
+        let mut state = self.lock();
+        let height = state.blocks.len() as u64 + 1;
+        let prev_hash = state
+            .blocks
+            .last()
+            .map(|stored| stored.block.header.hash)
+            .unwrap_or(MockHash([0; 32]));
+        let block = self.build_block(prev_hash, height, time, state.reorgs, &blobs);
+        state.blocks.push(StoredBlock { block, blobs });
+        if let Some(proof) = proof {
+            state.proofs.entry(height).or_default().push(proof);
+        }
+
+        if let Some(reorgs) = self.reorgs {
+            if reorgs.depth > 0 && height % reorgs.interval == 0 {
+                self.reorg(&mut state, reorgs.depth);
+            }
+        }
+
+        let finalized = height
+            .checked_sub(u64::from(self.blocks_to_finality))
+            .filter(|finalized| *finalized > 0)
+            .map(|finalized| state.blocks[finalized as usize - 1].block.header.clone());
+        drop(state);
+
+        self.head.send_replace(height);
+        if let Some(header) = finalized {
+            // Nobody may be subscribed
+            let _ = self.finalized_headers.send(header);
+        }
+        height
+    }
+
+    /// Replaces the last `depth` blocks by blocks with the same blobs and different hashes.
+    fn reorg(&self, state: &mut ChainState, depth: u32) {
+        state.reorgs += 1;
+        let first = state.blocks.len().saturating_sub(depth as usize);
+        for index in first..state.blocks.len() {
+            let prev_hash = match index {
+                0 => MockHash([0; 32]),
+                _ => state.blocks[index - 1].block.header.hash,
+            };
+            let stored = &state.blocks[index];
+            let header = &stored.block.header;
+            let block = self.build_block(
+                prev_hash,
+                header.height,
+                header.time.clone(),
+                state.reorgs,
+                &stored.blobs,
+            );
+            state.blocks[index].block = block;
+        }
+        debug!(
+            "Reorged the mock DA blocks from height {} to {}",
+            first + 1,
+            state.blocks.len()
+        );
+    }
+
+    fn build_block(
+        &self,
+        prev_hash: MockHash,
+        height: u64,
+        time: Time,
+        reorgs: u64,
+        blobs: &[(MockAddress, Vec<u8>)],
+    ) -> MockBlock {
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.0);
+        hasher.update(height.to_be_bytes());
+        hasher.update(reorgs.to_be_bytes());
+        let blobs = blobs
+            .iter()
+            .map(|(sender, data)| {
+                let blob_hash: [u8; 32] = Sha256::digest(data).into();
+                hasher.update(blob_hash);
+                MockBlob::new(data.clone(), *sender, blob_hash)
+            })
+            .collect();
+
+        MockBlock {
+            header: MockBlockHeader {
+                prev_hash,
+                hash: MockHash(hasher.finalize().into()),
+                height,
+                time,
+            },
+            validity_cond: MockValidityCond { is_valid: true },
+            blobs,
+        }
+    }
+
+    /// Waits until the block at `height` is final, and returns it.
+    async fn block_at(&self, height: u64) -> anyhow::Result<MockBlock> {
+        if height == 0 {
+            bail!("The mock DA blocks start at height 1");
+        }
+        let final_at = height + u64::from(self.blocks_to_finality);
+        self.head
+            .subscribe()
+            .wait_for(|head| *head >= final_at)
+            .await
+            .map_err(|_| anyhow!("The mock DA layer no longer produces blocks"))?;
+        Ok(self.lock().blocks[height as usize - 1].block.clone())
+    }
+
+    /// The header at `height`, or the header before the first block for height 0.
+    fn header_at(&self, state: &ChainState, height: u64) -> MockBlockHeader {
+        match height {
+            0 => MockBlockHeader {
+                prev_hash: MockHash([0; 32]),
+                hash: MockHash([0; 32]),
+                height: 0,
+                time: Time::from_secs(0),
+            },
+            height => state.blocks[height as usize - 1].block.header.clone(),
+        }
+    }
+
+    fn head_header(&self) -> MockBlockHeader {
+        let state = self.lock();
+        self.header_at(&state, state.blocks.len() as u64)
+    }
+
+    fn last_finalized_header(&self) -> MockBlockHeader {
+        let state = self.lock();
+        let finalized =
+            (state.blocks.len() as u64).saturating_sub(u64::from(self.blocks_to_finality));
+        self.header_at(&state, finalized)
+    }
+
+    fn proofs_at(&self, height: u64) -> Vec<Vec<u8>> {
+        self.lock().proofs.get(&height).cloned().unwrap_or_default()
+    }
+}
+
+/// The state shared by the handles of the service and the block producer.
+struct Shared {
+    /// The DA address the blobs of this node are submitted from.
+    sender_address: MockAddress,
+    log: MockDaLog,
+    production: BlockProduction,
+    /// Identifies the proofs submitted by this node in the log.
+    writer: u64,
+    /// The position up to which the log has been applied.
+    applied: watch::Receiver<u64>,
+    /// The DA heights of the proofs submitted by this node, by position of the end of their
+    /// record.
+    proof_heights: Mutex<HashMap<u64, u64>>,
+    /// Wakes the block producer up when the node submits something.
+    submitted: Notify,
+}
+
+/// A mock DA layer with configurable block production, optionally persisted to a local
+/// directory.
+#[derive(Clone)]
+pub struct PersistentMockDaService {
+    chain: Arc<MockChain>,
+    shared: Arc<Shared>,
+}
+
+impl PersistentMockDaService {
+    /// Creates an in-memory mock DA layer.
+    pub fn in_memory(
+        sender_address: MockAddress,
+        production: BlockProduction,
+    ) -> anyhow::Result<Self> {
+        production.check()?;
+        let (service, producer) = Self::new(
+            sender_address,
+            MockDaLog::Memory(Mutex::new(Vec::new())),
+            production,
+        );
+        tokio::spawn(producer.run());
+        Ok(service)
+    }
+
+    /// Opens the mock DA layer stored in `dir`, creating it if needed, and applies the
+    /// submissions it already contains.
+    pub async fn open(
+        sender_address: MockAddress,
+        dir: &Path,
+        production: BlockProduction,
+    ) -> anyhow::Result<Self> {
+        production.check()?;
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create the mock DA directory {}", dir.display()))?;
+        let path = dir.join(LOG_FILE_NAME);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open the mock DA log {}", path.display()))?;
+
+        let (service, mut producer) = Self::new(sender_address, MockDaLog::File(path), production);
+        // The blocks of a previous run are produced at once
+        let blocks = producer.produce_pending_blocks(&service.shared)?;
+        debug!("Restored {} mock DA blocks", blocks);
+        tokio::spawn(producer.run());
+        Ok(service)
+    }
+
+    fn new(
+        sender_address: MockAddress,
+        log: MockDaLog,
+        production: BlockProduction,
+    ) -> (Self, BlockProducer) {
+        let chain = Arc::new(MockChain::new(&production));
+        let (applied_sender, applied) = watch::channel(0);
+        let shared = Arc::new(Shared {
+            sender_address,
+            log,
+            production,
+            writer: rand_writer_id(),
+            applied,
+            proof_heights: Mutex::new(HashMap::new()),
+            submitted: Notify::new(),
+        });
+
+        let producer = BlockProducer {
+            chain: chain.clone(),
+            shared: Arc::downgrade(&shared),
+            applied: applied_sender,
+            pending: VecDeque::new(),
+            read_position: 0,
+            last_block: Instant::now(),
+        };
+        (Self { chain, shared }, producer)
+    }
+
+    /// Appends a record to the log and waits until its block has been produced, returning the
+    /// position of the end of the record.
+    async fn submit(&self, record: &MockDaRecord) -> anyhow::Result<u64> {
+        let end = self.shared.log.append(record)?;
+        self.shared.submitted.notify_one();
+
+        let mut applied = self.shared.applied.clone();
+        applied
+            .wait_for(|applied| *applied >= end)
+            .await
+            .map_err(|_| anyhow!("The mock DA layer no longer produces blocks"))?;
+        Ok(end)
+    }
+}
+
+/// Produces the blocks of the submissions in the log.
+struct BlockProducer {
+    chain: Arc<MockChain>,
+    shared: Weak<Shared>,
+    applied: watch::Sender<u64>,
+    /// The records read from the log without a block yet, with the positions of their ends.
+    pending: VecDeque<(MockDaRecord, u64)>,
+    /// The position up to which the log has been read.
+    read_position: u64,
+    last_block: Instant,
+}
+
+impl BlockProducer {
+    /// Produces blocks until the service is dropped.
+    async fn run(mut self) {
+        while let Some(shared) = self.shared.upgrade() {
+            if let Err(e) = self.produce_blocks(&shared) {
+                warn!("Failed to produce a mock DA block: {:?}", e);
+            }
+
+            let next_block_in = match shared.production.block_time {
+                Some(block_time) => block_time.saturating_sub(self.last_block.elapsed()),
+                None => POLL_INTERVAL,
+            };
+            tokio::select! {
+                _ = shared.submitted.notified() => {}
+                _ = tokio::time::sleep(next_block_in) => {}
+            }
+        }
+    }
+
+    /// Produces the blocks that are due: a single one when a block time is configured, and one
+    /// per pending submission otherwise.
+    fn produce_blocks(&mut self, shared: &Shared) -> anyhow::Result<()> {
+        let Some(block_time) = shared.production.block_time else {
+            self.produce_pending_blocks(shared)?;
+            return Ok(());
+        };
+        if self.last_block.elapsed() < block_time {
+            return Ok(());
+        }
+
+        self.read_new_records(shared)?;
+        if self.pending.is_empty() && shared.production.produce_empty_blocks {
+            // Empty blocks are logged like submissions, so the nodes sharing the log see them
+            shared
+                .log
+                .append(&MockDaRecord::EmptyBlock { time: now_secs() })?;
+            self.read_new_records(shared)?;
+        }
+        if let Some((record, end)) = self.pending.pop_front() {
+            self.produce_block(shared, record, end);
+        }
+        Ok(())
+    }
+
+    /// Produces a block for every pending submission, returning how many there were.
+    fn produce_pending_blocks(&mut self, shared: &Shared) -> anyhow::Result<usize> {
+        self.read_new_records(shared)?;
+        let blocks = self.pending.len();
+        while let Some((record, end)) = self.pending.pop_front() {
+            self.produce_block(shared, record, end);
+        }
+        Ok(blocks)
+    }
+
+    fn read_new_records(&mut self, shared: &Shared) -> anyhow::Result<()> {
+        for (record, end) in shared.log.read_from(self.read_position)? {
+            self.read_position = end;
+            self.pending.push_back((record, end));
+        }
+        Ok(())
+    }
+
+    fn produce_block(&mut self, shared: &Shared, record: MockDaRecord, end: u64) {
+        match record {
+            MockDaRecord::Blob { sender, time, data } => {
+                self.chain
+                    .produce_block(Time::from_secs(time), vec![(sender, data)], None);
+            }
+            MockDaRecord::EmptyBlock { time } => {
+                self.chain
+                    .produce_block(Time::from_secs(time), Vec::new(), None);
+            }
+            MockDaRecord::Proof { writer, time, data } => {
+                let height =
+                    self.chain
+                        .produce_block(Time::from_secs(time), Vec::new(), Some(data));
+                if writer == shared.writer {
+                    shared
+                        .proof_heights
+                        .lock()
+                        .expect("Mock DA lock poisoned")
+                        .insert(end, height);
+                }
+            }
+        }
+        self.last_block = Instant::now();
+        self.applied.send_replace(end);
+    }
+}
+
+fn rand_writer_id() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.finish()
+}
+
+#[async_trait]
+impl DaService for PersistentMockDaService {
+    type Spec = MockDaSpec;
+    type Verifier = MockDaVerifier;
+    type FilteredBlock = MockBlock;
+    type HeaderStream =
+        Pin<Box<dyn Stream<Item = Result<MockBlockHeader, Self::Error>> + Send + 'static>>;
+    type Error = anyhow::Error;
+
+    async fn get_block_at(&self, height: u64) -> Result<Self::FilteredBlock, Self::Error> {
+        self.chain.block_at(height).await
+    }
+
+    async fn get_last_finalized_block_header(
+        &self,
+    ) -> Result<<Self::Spec as DaSpec>::BlockHeader, Self::Error> {
+        Ok(self.chain.last_finalized_header())
+    }
+
+    async fn subscribe_finalized_header(&self) -> Result<Self::HeaderStream, Self::Error> {
+        let receiver = self.chain.finalized_headers.subscribe();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(header) => return Some((Ok(header), receiver)),
+                    // A slow subscriber skips the headers it missed
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    async fn get_head_block_header(
+        &self,
+    ) -> Result<<Self::Spec as DaSpec>::BlockHeader, Self::Error> {
+        Ok(self.chain.head_header())
+    }
+
+    fn extract_relevant_blobs(
+        &self,
+        block: &Self::FilteredBlock,
+    ) -> Vec<<Self::Spec as DaSpec>::BlobTransaction> {
+        block.blobs.clone()
+    }
+
+    async fn get_extraction_proof(
+        &self,
+        _block: &Self::FilteredBlock,
+        _blobs: &[<Self::Spec as DaSpec>::BlobTransaction],
+    ) -> (
+        <Self::Spec as DaSpec>::InclusionMultiProof,
+        <Self::Spec as DaSpec>::CompletenessProof,
+    ) {
+        // The mock DA verifier accepts any blobs
+        ([0; 32], ())
+    }
+
+    async fn send_transaction(&self, blob: &[u8]) -> Result<(), Self::Error> {
+        if let Some(max_blob_size) = self.shared.production.max_blob_size {
+            if blob.len() > max_blob_size {
+                bail!(
+                    "The blob of {} bytes exceeds the maximum blob size of {} bytes",
+                    blob.len(),
+                    max_blob_size
+                );
+            }
+        }
+        let record = MockDaRecord::Blob {
+            sender: self.shared.sender_address,
+            time: now_secs(),
+            data: blob.to_vec(),
+        };
+        self.submit(&record).await?;
+        Ok(())
+    }
+
+    async fn send_aggregated_zk_proof(
+        &self,
+        aggregated_proof_data: &[u8],
+    ) -> Result<u64, Self::Error> {
+        let record = MockDaRecord::Proof {
+            writer: self.shared.writer,
+            time: now_secs(),
+            data: aggregated_proof_data.to_vec(),
+        };
+        let end = self.submit(&record).await?;
+        self.shared
+            .proof_heights
+            .lock()
+            .expect("Mock DA lock poisoned")
+            .remove(&end)
+            .ok_or_else(|| anyhow!("The proof was not included in a block"))
+    }
+
+    async fn get_aggregated_proofs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self.chain.proofs_at(height))
+    }
+}
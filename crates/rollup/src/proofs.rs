@@ -0,0 +1,400 @@
+#![deny(missing_docs)]
+//! Publication of the rollup proofs on the DA layer, and verification of the published proofs.
+//!
+//! The prover service proves every slot separately. The [`ProofTap`] intercepts the proofs the
+//! zkVM produces, and the [`ProofPublisher`] bundles every `aggregated_proof_block_jump` of them,
+//! and the last ones when the prover stops, into an [`AggregatedProof`] blob, posted to the proof
+//! namespace of the DA layer. The proofs are not recursively aggregated: the blob is only the list
+//! of the slot proofs, each of which is verified on its own. The last slot covered by a published
+//! proof is recorded in the storage of the node, for the `prover_window` pruning policy.
+//! The [`ProofFollower`] reads these blobs back from the finalized DA blocks and verifies them,
+//! so that the rollup can be followed from the DA layer alone. The last proof it verified is
+//! reported by the sync status of the node.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{Address, DaSpec};
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_interface::zk::{Proof, StateTransition, Zkvm, ZkvmHost};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::metrics::{ProverQueue, SubmittedSlot};
+use crate::pruning::record_proven_slot;
+
+/// The proofs of consecutive slots, posted to the DA layer as a single blob.
+///
+/// This is a plain list of the slot proofs, not a recursive proof of all of them.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AggregatedProof {
+    /// The serialized proofs, in slot order.
+    pub proofs: Vec<Vec<u8>>,
+}
+
+/// The outcome of proving a slot, sent by a [`ProofTap`] to the [`ProofPublisher`].
+#[derive(Debug)]
+pub struct SlotProof {
+    /// The position of the slot among the slots handed to the prover since the node started.
+    pub slot: u64,
+    /// The serialized proof, or `None` if the slot was not proven.
+    pub proof: Option<Vec<u8>>,
+}
+
+/// A zkVM host forwarding every proof it produces to a [`ProofPublisher`].
+///
+/// The prover service clones the host and adds the hints of a slot to the clone before proving
+/// it, in slot order, so the tap numbers the slots as their hints are added. The proofs complete
+/// out of order, and the publisher uses these numbers to put them back in slot order. The slot
+/// stays in the [`ProverQueue`] until it is proven, so the node waits for it before stopping.
+#[derive(Clone)]
+pub struct ProofTap<Vm> {
+    inner: Vm,
+    proofs: mpsc::UnboundedSender<SlotProof>,
+    queue: ProverQueue,
+    next_slot: Arc<AtomicU64>,
+    slot: Option<u64>,
+    submitted: Option<Arc<SubmittedSlot>>,
+}
+
+impl<Vm> ProofTap<Vm> {
+    /// Wraps `inner`, sending its proofs to `proofs` and recording the slots it starts to prove
+    /// in `queue`.
+    pub fn new(inner: Vm, proofs: mpsc::UnboundedSender<SlotProof>, queue: ProverQueue) -> Self {
+        Self {
+            inner,
+            proofs,
+            queue,
+            next_slot: Arc::new(AtomicU64::new(0)),
+            slot: None,
+            submitted: None,
+        }
+    }
+
+    fn send(&self, proof: Option<Vec<u8>>) {
+        let Some(slot) = self.slot else {
+            warn!("A proof was produced without the hints of a slot, it will not be posted to DA");
+            return;
+        };
+        if self.proofs.send(SlotProof { slot, proof }).is_err() {
+            warn!("The proof publisher has stopped, the proof will not be posted to DA");
+        }
+    }
+}
+
+impl<Vm: Zkvm> Zkvm for ProofTap<Vm> {
+    type CodeCommitment = Vm::CodeCommitment;
+    type Error = Vm::Error;
+
+    fn verify<'a>(
+        serialized_proof: &'a [u8],
+        code_commitment: &Self::CodeCommitment,
+    ) -> Result<&'a [u8], Self::Error> {
+        Vm::verify(serialized_proof, code_commitment)
+    }
+
+    fn verify_and_extract_output<
+        Add: sov_rollup_interface::RollupAddress,
+        Da: DaSpec,
+        Root: Serialize + DeserializeOwned,
+    >(
+        serialized_proof: &[u8],
+        code_commitment: &Self::CodeCommitment,
+    ) -> Result<StateTransition<Da, Add, Root>, Self::Error> {
+        Vm::verify_and_extract_output::<Add, Da, Root>(serialized_proof, code_commitment)
+    }
+}
+
+impl<Vm: ZkvmHost> ZkvmHost for ProofTap<Vm> {
+    type Guest = Vm::Guest;
+
+    fn add_hint<T: Serialize>(&mut self, item: T) {
+        if self.slot.is_none() {
+            self.slot = Some(self.next_slot.fetch_add(1, Ordering::SeqCst));
+            self.submitted = Some(Arc::new(self.queue.submit()));
+        }
+        self.inner.add_hint(item)
+    }
+
+    fn simulate_with_hints(&mut self) -> Self::Guest {
+        let _submitted = self.submitted.take();
+        self.queue.start_proving();
+        self.inner.simulate_with_hints()
+    }
+
+    fn run(&mut self, with_proof: bool) -> Result<Proof, anyhow::Error> {
+        // The slot is proven once its proof is handed to the publisher
+        let _submitted = self.submitted.take();
+        self.queue.start_proving();
+        // Slots without a proof are reported too, so that the publisher does not wait for them
+        let proof = match self.inner.run(with_proof) {
+            Ok(proof) => proof,
+            Err(e) => {
+                self.send(None);
+                return Err(e);
+            }
+        };
+        match &proof {
+            Proof::Full(serialized_proof) => self.send(Some(serialized_proof.clone())),
+            Proof::PublicInput(_) => self.send(None),
+        }
+        Ok(proof)
+    }
+}
+
+/// Where a [`ProofPublisher`] records the last rollup slot covered by its published proofs.
+#[derive(Debug, Clone)]
+pub struct ProvenSlotRecord {
+    /// The storage of the node.
+    pub storage_path: PathBuf,
+    /// The rollup slot of the first slot the [`ProofTap`] numbers: the slot after the last slot
+    /// of the ledger when the rollup was created. Read when a proof is published.
+    pub first_slot: Arc<AtomicU64>,
+}
+
+impl ProvenSlotRecord {
+    fn record(&self, slot: u64) {
+        let slot = self.first_slot.load(Ordering::SeqCst) + slot;
+        if let Err(e) = record_proven_slot(&self.storage_path, slot) {
+            warn!("Failed to record that slot {} is proven: {:?}", slot, e);
+        }
+    }
+}
+
+/// Posts the proofs received from a [`ProofTap`] to the DA layer.
+pub struct ProofPublisher<Da: DaService> {
+    da_service: Da,
+    aggregated_proof_block_jump: u64,
+    proofs: mpsc::UnboundedReceiver<SlotProof>,
+    proven_slots: Option<ProvenSlotRecord>,
+}
+
+impl<Da: DaService> ProofPublisher<Da> {
+    /// Creates a publisher posting an [`AggregatedProof`] every `aggregated_proof_block_jump`
+    /// proofs, and recording the last slot they cover in `proven_slots`, if any.
+    pub fn new(
+        da_service: Da,
+        aggregated_proof_block_jump: u64,
+        proofs: mpsc::UnboundedReceiver<SlotProof>,
+        proven_slots: Option<ProvenSlotRecord>,
+    ) -> Self {
+        Self {
+            da_service,
+            aggregated_proof_block_jump: aggregated_proof_block_jump.max(1),
+            proofs,
+            proven_slots,
+        }
+    }
+
+    /// Publishes proofs until every [`ProofTap`] is dropped, then publishes the remaining
+    /// proofs, even if there are fewer than `aggregated_proof_block_jump` of them.
+    ///
+    /// The proofs are published in slot order: a proof completed before the proofs of the
+    /// previous slots waits for them.
+    pub async fn run(mut self) {
+        let mut next_slot = 0;
+        let mut out_of_order = BTreeMap::new();
+        let mut pending = Vec::new();
+
+        while let Some(slot_proof) = self.proofs.recv().await {
+            out_of_order.insert(slot_proof.slot, slot_proof.proof);
+            while let Some(proof) = out_of_order.remove(&next_slot) {
+                next_slot += 1;
+                match proof {
+                    Some(proof) => pending.push((next_slot - 1, proof)),
+                    None => debug!("Slot {} was not proven", next_slot - 1),
+                }
+            }
+            if (pending.len() as u64) >= self.aggregated_proof_block_jump {
+                self.publish(std::mem::take(&mut pending)).await;
+            }
+        }
+
+        // The taps of the slots that were never reported are gone, so the proofs after them are
+        // published without them
+        if let Some(missing) = out_of_order.keys().next() {
+            warn!(
+                "The proofs of the slots {} to {} were never produced",
+                next_slot,
+                missing - 1
+            );
+        }
+        pending.extend(
+            out_of_order
+                .into_iter()
+                .filter_map(|(slot, proof)| Some((slot, proof?))),
+        );
+        if !pending.is_empty() {
+            self.publish(pending).await;
+        }
+    }
+
+    /// Publishes the proofs of the given slots, in slot order.
+    async fn publish(&self, proofs: Vec<(u64, Vec<u8>)>) {
+        let last_slot = proofs.last().map(|(slot, _)| *slot);
+        let aggregated_proof = AggregatedProof {
+            proofs: proofs.into_iter().map(|(_, proof)| proof).collect(),
+        };
+        let blob = aggregated_proof
+            .try_to_vec()
+            .expect("Aggregated proof serialization cannot fail");
+
+        match self.da_service.send_aggregated_zk_proof(&blob).await {
+            Ok(height) => {
+                info!(
+                    "Posted an aggregated proof of {} slots to DA at height {}",
+                    aggregated_proof.proofs.len(),
+                    height
+                );
+                if let (Some(proven_slots), Some(last_slot)) = (&self.proven_slots, last_slot) {
+                    proven_slots.record(last_slot);
+                }
+            }
+            Err(e) => warn!("Failed to post an aggregated proof to DA: {:?}", e),
+        }
+    }
+}
+
+/// A proof the [`ProofFollower`] verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedProof {
+    /// The DA height the proof was posted at.
+    pub da_height: u64,
+    /// The hex-encoded hash of the last DA block the proof covers.
+    pub slot_hash: String,
+    /// The hex-encoded state root the proof ends at.
+    pub state_root: String,
+}
+
+/// The last proof the [`ProofFollower`] verified, shared with the status of the node.
+#[derive(Debug, Clone, Default)]
+pub struct LastVerifiedProof(Arc<Mutex<Option<VerifiedProof>>>);
+
+impl LastVerifiedProof {
+    /// The last verified proof, if any proof was verified.
+    pub fn get(&self) -> Option<VerifiedProof> {
+        self.0.lock().expect("Verified proof lock poisoned").clone()
+    }
+
+    fn set(&self, proof: VerifiedProof) {
+        *self.0.lock().expect("Verified proof lock poisoned") = Some(proof);
+    }
+}
+
+/// Reads the aggregated proofs posted to the DA layer, and verifies them.
+///
+/// Only finalized DA blocks are read, so the proofs it verified are never reorged out.
+pub struct ProofFollower<Da: DaService, Vm: Zkvm> {
+    da_service: Da,
+    code_commitment: Vm::CodeCommitment,
+    next_height: u64,
+    last_verified: LastVerifiedProof,
+}
+
+impl<Da, Vm> ProofFollower<Da, Vm>
+where
+    Da: DaService,
+    Vm: Zkvm,
+{
+    /// Creates a follower verifying the proofs posted from `start_height` onwards against the
+    /// given guest code, and recording the last verified proof in `last_verified`.
+    pub fn new(
+        da_service: Da,
+        code_commitment: Vm::CodeCommitment,
+        start_height: u64,
+        last_verified: LastVerifiedProof,
+    ) -> Self {
+        Self {
+            da_service,
+            code_commitment,
+            next_height: start_height,
+            last_verified,
+        }
+    }
+
+    /// Follows the DA layer forever, recording the state transitions of the verified proofs.
+    pub async fn run<Root: Serialize + DeserializeOwned + AsRef<[u8]>>(mut self) {
+        loop {
+            // Wait until the DA block is final
+            match self.da_service.get_last_finalized_block_header().await {
+                Ok(header) if header.height() >= self.next_height => {}
+                Ok(_) => {
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to fetch the last finalized DA block: {:?}", e);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            }
+
+            let blobs = match self
+                .da_service
+                .get_aggregated_proofs_at(self.next_height)
+                .await
+            {
+                Ok(blobs) => blobs,
+                Err(e) => {
+                    warn!(
+                        "Failed to read the proofs at DA height {}: {:?}",
+                        self.next_height, e
+                    );
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            for blob in blobs {
+                self.verify_blob::<Root>(&blob);
+            }
+            self.next_height += 1;
+        }
+    }
+
+    fn verify_blob<Root: Serialize + DeserializeOwned + AsRef<[u8]>>(&self, blob: &[u8]) {
+        let aggregated_proof = match AggregatedProof::try_from_slice(blob) {
+            Ok(aggregated_proof) => aggregated_proof,
+            Err(e) => {
+                warn!(
+                    "Malformed aggregated proof at DA height {}: {}",
+                    self.next_height, e
+                );
+                return;
+            }
+        };
+
+        for proof in aggregated_proof.proofs {
+            match Vm::verify_and_extract_output::<Address, Da::Spec, Root>(
+                &proof,
+                &self.code_commitment,
+            ) {
+                Ok(transition) => {
+                    let state_root = hex::encode(transition.final_state_root.as_ref());
+                    info!(
+                        "Verified proof posted at DA height {}: state root 0x{} -> 0x{}",
+                        self.next_height,
+                        hex::encode(transition.initial_state_root.as_ref()),
+                        state_root
+                    );
+                    let slot_hash: [u8; 32] = transition.slot_hash.into();
+                    self.last_verified.set(VerifiedProof {
+                        da_height: self.next_height,
+                        slot_hash: hex::encode(slot_hash),
+                        state_root,
+                    });
+                }
+                Err(e) => warn!(
+                    "Invalid proof posted at DA height {}: {:?}",
+                    self.next_height, e
+                ),
+            }
+        }
+    }
+}
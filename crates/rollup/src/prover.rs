@@ -0,0 +1,72 @@
+#![deny(missing_docs)]
+//! Selection of the prover mode a node runs in.
+
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+use sov_stf_runner::{from_toml_path, RollupProverConfig};
+
+/// How the node proves the slots it processes.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverMode {
+    /// Do not run the prover at all.
+    Skip,
+    /// Run the guest code natively, without the zkVM.
+    Simulate,
+    /// Run the guest code in the zkVM executor, without generating proofs.
+    #[default]
+    Execute,
+    /// Run the guest code in the zkVM and generate proofs.
+    Prove,
+}
+
+impl From<ProverMode> for RollupProverConfig {
+    fn from(mode: ProverMode) -> Self {
+        match mode {
+            ProverMode::Skip => RollupProverConfig::Skip,
+            ProverMode::Simulate => RollupProverConfig::Simulate,
+            ProverMode::Execute => RollupProverConfig::Execute,
+            ProverMode::Prove => RollupProverConfig::Prove,
+        }
+    }
+}
+
+/// The `[prover]` section of the rollup config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverConfig {
+    /// The prover mode of the node.
+    pub mode: ProverMode,
+}
+
+#[derive(Deserialize)]
+struct ProverSection {
+    #[serde(default)]
+    prover: Option<ProverConfig>,
+}
+
+impl ProverConfig {
+    /// Reads the `[prover]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: ProverSection =
+            from_toml_path(path).context("Failed to read the `[prover]` section")?;
+        Ok(section.prover)
+    }
+}
+
+impl ProverMode {
+    /// Checks that the guest code required by this mode has been built.
+    ///
+    /// The guest ELF is empty when the `risc0-starter` crate is built with `SKIP_GUEST_BUILD`.
+    pub fn check_guest_elf(self, guest_elf: &[u8]) -> anyhow::Result<()> {
+        if self == ProverMode::Prove && guest_elf.is_empty() {
+            bail!(
+                "The prover cannot run in `prove` mode: the guest ELF is empty because the node \
+                was built with `SKIP_GUEST_BUILD` set. Rebuild without it, or pick another \
+                `--prover-mode`"
+            );
+        }
+        Ok(())
+    }
+}
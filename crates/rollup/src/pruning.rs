@@ -0,0 +1,397 @@
+#![deny(missing_docs)]
+//! Pruning of the historical state versions, configured by the `[storage.pruning]` section of
+//! the rollup config, and the on-disk statistics of the databases.
+//!
+//! Every slot writes a new version of the JMT state, and the storage of the SDK keeps all of
+//! them. Pruning removes the values that are shadowed by a newer version of the same key older
+//! than the versions to keep, so the state stays readable from the oldest kept version on. The
+//! values are keyed by the state key followed by the big-endian version, so the versions of a key
+//! are adjacent and ordered.
+//!
+//! Only the values are pruned. The JMT nodes of every version stay in the `jmt_nodes` column
+//! family, which keeps growing with every slot: the state roots of every height remain available,
+//! but pruning only bounds the size of the values, not the size of the state database.
+//!
+//! The SDK has no API to delete state versions, so the values are deleted from the `jmt_values`
+//! column family of the state database directly. Nothing is deleted if the column families are
+//! not the ones of the SDK this node is built with. [`check_state_schema`] also checks the layout
+//! of every value key, which reads the whole column family, so the node only runs it when it
+//! starts, and `node db prune` before pruning.
+//!
+//! The storage of the SDK holds the only handle to the state database while the node runs, so
+//! the running node pauses its runner every `pruning_interval` slots, closes the databases,
+//! prunes and resumes. It also prunes when it starts, and `node db prune` prunes a stopped node.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+use sov_db::ledger_db::LedgerDB;
+use sov_stf_runner::from_toml_path;
+use tracing::info;
+
+/// The directory of the state database in the storage of the node.
+const STATE_DB_DIR: &str = "state";
+/// The column family of the JMT values in the state database.
+const JMT_VALUES_CF: &str = "jmt_values";
+/// The column families of the state database of the SDK, sorted.
+const STATE_COLUMN_FAMILIES: [&str; 4] = ["default", "jmt_nodes", JMT_VALUES_CF, "key_hash_to_key"];
+/// Records the oldest readable version once the state is pruned.
+const PRUNED_FILE: &str = "pruned.json";
+/// Records the last slot covered by a proof published to the DA layer.
+const PROVEN_FILE: &str = "proven.json";
+/// The deletions written to the state database at once.
+const DELETE_BATCH_SIZE: usize = 10_000;
+
+/// Which state versions the node keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PruningPolicy {
+    /// Keeps every version, so the state of every height can be read.
+    #[default]
+    Archive,
+    /// Keeps the last `versions` versions.
+    KeepLast {
+        /// The number of versions to keep.
+        versions: u64,
+    },
+    /// Keeps the versions the prover may still need: from the state after the last slot covered
+    /// by a published proof, which the next slot is proven from. Nothing is pruned before the
+    /// first proof is published, so a node that does not prove keeps every version.
+    ProverWindow,
+}
+
+/// The pruning settings of the `[storage]` section of the rollup config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruningConfig {
+    /// Which versions to keep, from the `[storage.pruning]` section.
+    pub policy: PruningPolicy,
+    /// The slots the running node processes between two prunings, from `pruning_interval`.
+    pub interval: u64,
+}
+
+#[derive(Deserialize)]
+struct StorageSection {
+    storage: PruningSection,
+}
+
+#[derive(Deserialize)]
+struct PruningSection {
+    #[serde(default)]
+    pruning: Option<PruningPolicy>,
+    #[serde(default = "default_pruning_interval")]
+    pruning_interval: u64,
+}
+
+fn default_pruning_interval() -> u64 {
+    1000
+}
+
+impl PruningConfig {
+    /// Reads the pruning settings of the `[storage]` section of the rollup config. Without a
+    /// `[storage.pruning]` section, every version is kept.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let section: StorageSection = from_toml_path(path)
+            .context("Failed to read the pruning settings of the `[storage]` section")?;
+        Ok(Self {
+            policy: section.storage.pruning.unwrap_or_default(),
+            interval: section.storage.pruning_interval.max(1),
+        })
+    }
+
+    /// The DA height after which the running node pauses to prune, `interval` slots after the
+    /// last slot of its storage, or `None` if the policy keeps every version.
+    pub fn pause_height(
+        &self,
+        storage_path: &Path,
+        start_height: u64,
+    ) -> anyhow::Result<Option<u64>> {
+        if self.policy == PruningPolicy::Archive {
+            return Ok(None);
+        }
+        // Slot `N` is DA block `start_height + N - 1`
+        Ok(Some(
+            start_height + head_slot(storage_path)?.unwrap_or_default() + self.interval - 1,
+        ))
+    }
+}
+
+impl PruningPolicy {
+    /// The number of versions of the stopped node in `storage_path` to keep, if the policy
+    /// prunes.
+    pub fn versions_to_keep(&self, storage_path: &Path) -> anyhow::Result<Option<u64>> {
+        Ok(match self {
+            PruningPolicy::Archive => None,
+            PruningPolicy::KeepLast { versions } => Some((*versions).max(1)),
+            PruningPolicy::ProverWindow => {
+                // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+                let oldest_needed_version = last_proven_slot(storage_path)?.unwrap_or_default() + 1;
+                let head_version = head_slot(storage_path)?.unwrap_or_default() + 1;
+                Some((head_version + 1).saturating_sub(oldest_needed_version))
+            }
+        })
+    }
+
+    /// Prunes the storage of a stopped node according to the policy.
+    pub fn apply(&self, storage_path: &Path) -> anyhow::Result<()> {
+        let Some(versions) = self.versions_to_keep(storage_path)? else {
+            return Ok(());
+        };
+        if let Some(pruned) = prune_state(storage_path, versions)? {
+            info!(
+                "Pruned {} state values, the state is readable from version {}",
+                pruned.deleted_values, pruned.oldest_readable_version
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of [`prune_state`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrunedState {
+    /// The oldest version the state can be read at.
+    pub oldest_readable_version: u64,
+    /// The number of values removed.
+    pub deleted_values: u64,
+}
+
+/// Prunes the state of a stopped node, keeping the last `versions` versions readable.
+///
+/// Returns `None` if there is nothing to prune: the node has not processed a slot yet, or the
+/// older versions were already pruned. Fails without deleting anything if the state database
+/// does not have the expected column families; the value keys are only checked by
+/// [`check_state_schema`].
+pub fn prune_state(storage_path: &Path, versions: u64) -> anyhow::Result<Option<PrunedState>> {
+    let Some(head_slot) = head_slot(storage_path)? else {
+        return Ok(None);
+    };
+
+    // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+    let head_version = head_slot + 1;
+    let oldest_readable_version = (head_version + 1).saturating_sub(versions.max(1));
+    if oldest_readable_version <= oldest_readable_version_of(storage_path)? {
+        return Ok(None);
+    }
+
+    check_column_families(storage_path)?;
+    let db = open_state_db(storage_path)?;
+    let values = db
+        .cf_handle(JMT_VALUES_CF)
+        .expect("The column families were checked");
+
+    let mut deleted_values = 0;
+    let mut batch = WriteBatch::default();
+    // The newest version of the current key that is not newer than the oldest readable one
+    let mut shadowing: Option<Box<[u8]>> = None;
+    for entry in db.iterator_cf(values, IteratorMode::Start) {
+        let (key, _) = entry?;
+        let Some((state_key, version)) = split_version(&key) else {
+            continue;
+        };
+        if version > oldest_readable_version {
+            shadowing = None;
+            continue;
+        }
+
+        if let Some(previous) = shadowing.replace(key.clone()) {
+            if split_version(&previous).map(|(previous_key, _)| previous_key) == Some(state_key) {
+                batch.delete_cf(values, previous);
+                deleted_values += 1;
+            }
+        }
+        if batch.len() >= DELETE_BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+    db.compact_range_cf(values, None::<&[u8]>, None::<&[u8]>);
+    drop(db);
+
+    fs::write(
+        storage_path.join(PRUNED_FILE),
+        serde_json::to_string(&oldest_readable_version)?,
+    )?;
+    Ok(Some(PrunedState {
+        oldest_readable_version,
+        deleted_values,
+    }))
+}
+
+/// The oldest version the state of the node can be read at: 1 unless it was pruned.
+pub fn oldest_readable_version_of(storage_path: &Path) -> anyhow::Result<u64> {
+    let path = storage_path.join(PRUNED_FILE);
+    if !path.exists() {
+        return Ok(1);
+    }
+    let version =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&version).with_context(|| format!("Invalid {}", path.display()))
+}
+
+/// The last slot covered by a proof the node published, if it published one.
+pub fn last_proven_slot(storage_path: &Path) -> anyhow::Result<Option<u64>> {
+    let path = storage_path.join(PROVEN_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let slot =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&slot).with_context(|| format!("Invalid {}", path.display()))
+}
+
+/// Records that the proofs the node published cover the slots up to `slot`.
+pub fn record_proven_slot(storage_path: &Path, slot: u64) -> anyhow::Result<()> {
+    let path = storage_path.join(PROVEN_FILE);
+    fs::write(&path, serde_json::to_string(&slot)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The last slot of the ledger of a stopped node, if it processed one.
+fn head_slot(storage_path: &Path) -> anyhow::Result<Option<u64>> {
+    let ledger_db =
+        LedgerDB::with_path(storage_path).context("Failed to open the ledger database")?;
+    Ok(ledger_db
+        .get_head_slot()?
+        .map(|(slot_number, _)| slot_number.0))
+}
+
+/// Checks that the state database of a stopped node has the layout the pruning was written for:
+/// the column families of the state database of the SDK, and `jmt_values` keys ending with a
+/// version the node has written. Reads every value of the state. A node that has not processed a
+/// slot yet has nothing to prune, and is not checked.
+pub fn check_state_schema(storage_path: &Path) -> anyhow::Result<()> {
+    let Some(head_slot) = head_slot(storage_path)? else {
+        return Ok(());
+    };
+    check_column_families(storage_path)?;
+    let head_version = head_slot + 1;
+    let db = open_state_db(storage_path)?;
+    let values = db
+        .cf_handle(JMT_VALUES_CF)
+        .expect("The column families were checked");
+    for entry in db.iterator_cf(values, IteratorMode::Start) {
+        let (key, _) = entry?;
+        match split_version(&key) {
+            Some((_, version)) if version <= head_version => {}
+            _ => bail!(
+                "The state database holds a `{JMT_VALUES_CF}` key that does not end with a \
+                 version up to {head_version}: its schema is not the one the pruning was written \
+                 for"
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn check_column_families(storage_path: &Path) -> anyhow::Result<()> {
+    let mut column_families = DB::list_cf(&Options::default(), storage_path.join(STATE_DB_DIR))?;
+    column_families.sort();
+    if column_families != STATE_COLUMN_FAMILIES {
+        bail!(
+            "The state database has the column families {column_families:?}, not \
+             {STATE_COLUMN_FAMILIES:?}: its schema is not the one the pruning was written for"
+        );
+    }
+    Ok(())
+}
+
+fn split_version(key: &[u8]) -> Option<(&[u8], u64)> {
+    let split = key.len().checked_sub(8)?;
+    let (state_key, version) = key.split_at(split);
+    Some((state_key, u64::from_be_bytes(version.try_into().ok()?)))
+}
+
+fn open_state_db(storage_path: &Path) -> anyhow::Result<DB> {
+    let path = storage_path.join(STATE_DB_DIR);
+    let options = Options::default();
+    let column_families = DB::list_cf(&options, &path)
+        .with_context(|| format!("Failed to read the state database at {}", path.display()))?;
+    DB::open_cf(&options, &path, column_families)
+        .with_context(|| format!("Failed to open the state database at {}", path.display()))
+}
+
+/// The on-disk statistics of a column family.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnFamilyStats {
+    /// The name of the column family.
+    pub name: String,
+    /// The size of its SST files, in bytes.
+    pub size: u64,
+    /// The estimated number of keys.
+    pub estimated_keys: u64,
+}
+
+/// The on-disk statistics of a database of the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    /// The directory of the database in the storage of the node.
+    pub name: String,
+    /// The size of its directory, in bytes, including the logs not yet compacted.
+    pub size: u64,
+    /// Its column families.
+    pub column_families: Vec<ColumnFamilyStats>,
+}
+
+/// Reads the on-disk statistics of the databases in the storage of a stopped node.
+pub fn db_stats(storage_path: &Path) -> anyhow::Result<Vec<DatabaseStats>> {
+    let mut directories: Vec<PathBuf> = fs::read_dir(storage_path)
+        .with_context(|| format!("Failed to read {}", storage_path.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    directories.retain(|path| path.is_dir());
+    directories.sort();
+
+    let options = Options::default();
+    let mut stats = Vec::new();
+    for path in directories {
+        // Only the directories of RocksDB databases have column families
+        let Ok(names) = DB::list_cf(&options, &path) else {
+            continue;
+        };
+        let db = DB::open_cf_for_read_only(&options, &path, &names, false)
+            .with_context(|| format!("Failed to open the database at {}", path.display()))?;
+
+        let mut column_families = Vec::new();
+        for name in names {
+            let cf = db
+                .cf_handle(&name)
+                .expect("The column families were opened");
+            column_families.push(ColumnFamilyStats {
+                size: db
+                    .property_int_value_cf(cf, "rocksdb.total-sst-files-size")?
+                    .unwrap_or_default(),
+                estimated_keys: db
+                    .property_int_value_cf(cf, "rocksdb.estimate-num-keys")?
+                    .unwrap_or_default(),
+                name,
+            });
+        }
+
+        stats.push(DatabaseStats {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size: dir_size(&path)?,
+            column_families,
+        });
+    }
+    Ok(stats)
+}
+
+fn dir_size(path: &Path) -> anyhow::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
@@ -0,0 +1,152 @@
+#![deny(missing_docs)]
+//! Configuration of the sequencer a node runs as.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_db::ledger_db::LedgerDB;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{Address, BasicAddress, DaSpec, Spec, WorkingSet};
+use sov_sequencer_registry::{SequencerConfig, SequencerRegistry};
+use sov_stf_runner::from_toml_path;
+use stf_starter::genesis_config::GenesisPaths;
+use tracing::info;
+
+/// Reads the `[sequencer]` section of the rollup config and checks it against the genesis.
+pub fn read_sequencer_config<Da: DaSpec>(
+    rollup_config_path: impl AsRef<Path>,
+    genesis_paths: &GenesisPaths,
+) -> anyhow::Result<Option<CheckedSequencerConfig<Da::Address>>> {
+    let Some(config) = NodeSequencerConfig::<Da::Address>::from_toml_path(rollup_config_path)?
+    else {
+        return Ok(None);
+    };
+
+    let genesis: SequencerConfig<DefaultContext, Da> = genesis_paths
+        .read_sequencer_registry_config()
+        .context("Failed to read the sequencer registry genesis")?;
+
+    config.check_genesis(&genesis).map(Some)
+}
+
+/// The `[sequencer]` section of the rollup config.
+///
+/// Nodes without this section do not act as sequencers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSequencerConfig<DaAddress> {
+    /// The rollup address of the sequencer.
+    pub rollup_address: Address,
+    /// The DA address the sequencer submits batches from.
+    pub da_address: DaAddress,
+}
+
+#[derive(Deserialize)]
+struct SequencerSection<DaAddress> {
+    #[serde(default)]
+    sequencer: Option<NodeSequencerConfig<DaAddress>>,
+}
+
+impl<DaAddress: BasicAddress + DeserializeOwned> NodeSequencerConfig<DaAddress> {
+    /// Reads the `[sequencer]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: SequencerSection<DaAddress> =
+            from_toml_path(path).context("Failed to read the `[sequencer]` section")?;
+        Ok(section.sequencer)
+    }
+
+    /// Checks the sequencer against the genesis of the `sequencer_registry` module.
+    ///
+    /// A sequencer that is not registered at genesis is not rejected here, as it may have
+    /// registered later on. It will be checked against the rollup state instead.
+    pub fn check_genesis<Da: DaSpec<Address = DaAddress>>(
+        self,
+        genesis: &SequencerConfig<DefaultContext, Da>,
+    ) -> anyhow::Result<CheckedSequencerConfig<DaAddress>> {
+        let registered_at_genesis = genesis.seq_da_address == self.da_address;
+
+        if registered_at_genesis && genesis.seq_rollup_address != self.rollup_address {
+            bail!(
+                "Sequencer {} is registered at genesis with rollup address {}, but {} is configured",
+                self.da_address,
+                genesis.seq_rollup_address,
+                self.rollup_address
+            );
+        }
+
+        Ok(CheckedSequencerConfig {
+            config: self,
+            registered_at_genesis,
+        })
+    }
+}
+
+/// A [`NodeSequencerConfig`] that was checked against the genesis of the sequencer registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedSequencerConfig<DaAddress> {
+    config: NodeSequencerConfig<DaAddress>,
+    registered_at_genesis: bool,
+}
+
+impl<DaAddress: BasicAddress> CheckedSequencerConfig<DaAddress> {
+    /// The rollup address of the sequencer.
+    pub fn rollup_address(&self) -> Address {
+        self.config.rollup_address
+    }
+
+    /// The DA address of the sequencer.
+    pub fn da_address(&self) -> &DaAddress {
+        &self.config.da_address
+    }
+
+    /// Checks that the sequencer is registered in the current rollup state.
+    ///
+    /// Before the genesis has been applied, the sequencer must be part of the genesis.
+    pub fn check_registered<Da: DaSpec<Address = DaAddress>>(
+        &self,
+        storage: &<DefaultContext as Spec>::Storage,
+        ledger_db: &LedgerDB,
+    ) -> anyhow::Result<()> {
+        if ledger_db.get_head_slot()?.is_none() {
+            if !self.registered_at_genesis {
+                bail!(
+                    "Sequencer {} is not registered at genesis",
+                    self.config.da_address
+                );
+            }
+            info!(
+                "Running as genesis sequencer {} with rollup address {}",
+                self.config.da_address, self.config.rollup_address
+            );
+            return Ok(());
+        }
+
+        let registry = SequencerRegistry::<DefaultContext, Da>::default();
+        let mut working_set = WorkingSet::new(storage.clone());
+        let registered = registry
+            .sequencer_address(self.config.da_address.clone(), &mut working_set)
+            .map_err(|e| anyhow!("Failed to query the sequencer registry: {}", e))?
+            .address;
+
+        match registered {
+            Some(address) if address == self.config.rollup_address => {
+                info!(
+                    "Running as sequencer {} with rollup address {}",
+                    self.config.da_address, self.config.rollup_address
+                );
+                Ok(())
+            }
+            Some(address) => bail!(
+                "Sequencer {} is registered with rollup address {}, but {} is configured",
+                self.config.da_address,
+                address,
+                self.config.rollup_address
+            ),
+            None => bail!(
+                "Sequencer {} is not registered in the sequencer registry",
+                self.config.da_address
+            ),
+        }
+    }
+}
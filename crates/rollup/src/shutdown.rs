@@ -0,0 +1,363 @@
+#![deny(missing_docs)]
+//! Graceful shutdown of the node, and pauses of its runner.
+//!
+//! The runner of the SDK cannot be stopped between two slots, so the node stops it through its
+//! DA service: once a shutdown is requested, the [`GatedDaService`] no longer hands DA blocks to
+//! the runner, which parks after finishing its current slot. Every processed slot is then
+//! committed to the state and ledger databases. Once the prover has proven every slot handed to
+//! it, including those waiting for a free worker, the RPC server is stopped and the runner is
+//! dropped, along with its prover service, storage manager and ledger database, and so are the
+//! tasks holding the databases: RocksDB flushes and closes them. The proofs of all these slots
+//! are then published, the last ones in a smaller aggregated proof if needed, before the node
+//! exits. The runner does not prove the slots it processed before a restart, so they are all
+//! proven before the node stops.
+//!
+//! The runner is paused the same way, once it reaches a DA height, so the node can work on its
+//! closed databases, such as pruning them, before it creates a new rollup and resumes. A pause
+//! only delays the next slots, so it waits for the proofs as long as they take, unless the node
+//! is terminated meanwhile.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use sov_modules_api::DaSpec;
+use sov_modules_rollup_blueprint::{Rollup, RollupBlueprint};
+use sov_rollup_interface::services::da::DaService;
+use sov_stf_runner::RpcConfig;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::metrics::{start_rpc_server, ProverQueue};
+
+struct ShutdownState {
+    requested: watch::Sender<bool>,
+    /// The DA height after which the runner is parked, to pause it.
+    pause_height: watch::Sender<Option<u64>>,
+    runner_parked: watch::Sender<bool>,
+    prover_queue: ProverQueue,
+    /// The tasks to wait for once the runner is dropped.
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// The tasks holding the databases, stopped once the runner is dropped.
+    database_readers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Coordinates the graceful shutdown of a node.
+#[derive(Clone)]
+pub struct Shutdown {
+    state: Arc<ShutdownState>,
+}
+
+impl Shutdown {
+    /// Creates the shutdown of a node whose prover records its slots in `prover_queue`.
+    pub fn new(prover_queue: ProverQueue) -> Self {
+        Self {
+            state: Arc::new(ShutdownState {
+                requested: watch::channel(false).0,
+                pause_height: watch::channel(None).0,
+                runner_parked: watch::channel(false).0,
+                prover_queue,
+                tasks: Mutex::new(Vec::new()),
+                database_readers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Stops handing DA blocks to the runner.
+    pub fn request(&self) {
+        self.state.requested.send_replace(true);
+    }
+
+    /// Whether a shutdown was requested.
+    pub fn is_requested(&self) -> bool {
+        *self.state.requested.borrow()
+    }
+
+    /// Parks the runner once it has processed the DA block at `da_height`, so
+    /// [`run_until_shutdown`] stops the rollup and returns [`Stopped::Paused`].
+    pub fn pause_after(&self, da_height: u64) {
+        self.state.pause_height.send_replace(Some(da_height));
+    }
+
+    /// Lets the runner of the next rollup read DA blocks again after a pause.
+    pub fn resume(&self) {
+        self.state.pause_height.send_replace(None);
+        self.state.runner_parked.send_replace(false);
+    }
+
+    fn pauses_before(&self, da_height: u64) -> bool {
+        matches!(*self.state.pause_height.borrow(), Some(pause_height) if da_height > pause_height)
+    }
+
+    async fn requested(&self) {
+        let mut requested = self.state.requested.subscribe();
+        // The sender lives as long as `self`
+        let _ = requested.wait_for(|requested| *requested).await;
+    }
+
+    async fn runner_parked(&self) {
+        let mut parked = self.state.runner_parked.subscribe();
+        let _ = parked.wait_for(|parked| *parked).await;
+    }
+
+    /// Waits for `task` before the node exits.
+    pub fn track(&self, task: JoinHandle<()>) {
+        self.state
+            .tasks
+            .lock()
+            .expect("Shutdown lock poisoned")
+            .push(task);
+    }
+
+    async fn join_tasks(&self) {
+        let tasks = std::mem::take(&mut *self.state.tasks.lock().expect("Shutdown lock poisoned"));
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!("A task failed during shutdown: {:?}", e);
+            }
+        }
+    }
+
+    /// Stops `task`, which holds the ledger or state database, once the runner is dropped, so
+    /// the databases are closed before the node exits.
+    pub fn track_database_reader(&self, task: JoinHandle<()>) {
+        self.state
+            .database_readers
+            .lock()
+            .expect("Shutdown lock poisoned")
+            .push(task);
+    }
+
+    async fn stop_database_readers(&self) {
+        let readers = std::mem::take(
+            &mut *self
+                .state
+                .database_readers
+                .lock()
+                .expect("Shutdown lock poisoned"),
+        );
+        for reader in readers {
+            reader.abort();
+            // Dropping the task releases its handles, even if it panicked
+            let _ = reader.await;
+        }
+    }
+}
+
+/// Why [`run_until_shutdown`] stopped the rollup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stopped {
+    /// The signal completed, or the runner stopped by itself.
+    Shutdown,
+    /// The runner reached the DA height of [`Shutdown::pause_after`].
+    Paused,
+}
+
+/// Runs `rollup` until `signal` completes or the runner is paused, then stops it gracefully.
+///
+/// The RPC methods of the rollup are served on `rpc_config`, and the bound address is reported
+/// to `rpc_reporting_channel`. `shutdown` must be the [`Shutdown`] of the DA service of the
+/// rollup. On shutdown, fails if the current slot or the remaining proofs take longer than
+/// `timeout`. On pause, waits for the proofs without a timeout, unless `signal` completes
+/// meanwhile, which turns the pause into a shutdown.
+pub async fn run_until_shutdown<S: RollupBlueprint>(
+    rollup: Rollup<S>,
+    rpc_config: &RpcConfig,
+    rpc_reporting_channel: Option<oneshot::Sender<SocketAddr>>,
+    shutdown: &Shutdown,
+    signal: impl Future<Output = ()>,
+    timeout: Duration,
+) -> anyhow::Result<Stopped> {
+    let Rollup {
+        mut runner,
+        rpc_methods,
+    } = rollup;
+    let rpc_server = start_rpc_server(rpc_methods, rpc_config, rpc_reporting_channel).await?;
+
+    let mut run = Box::pin(runner.run_in_process());
+    let mut signal = std::pin::pin!(signal);
+    let mut stopped = tokio::select! {
+        result = &mut run => return result.map(|()| Stopped::Shutdown),
+        _ = &mut signal => Stopped::Shutdown,
+        _ = shutdown.runner_parked() => Stopped::Paused,
+    };
+
+    match stopped {
+        Stopped::Shutdown => {
+            info!("Shutting down, finishing the current slot");
+            shutdown.request();
+            tokio::time::timeout(timeout, async {
+                tokio::select! {
+                    result = &mut run => result,
+                    _ = shutdown.runner_parked() => Ok(()),
+                }
+            })
+            .await
+            .with_context(|| format!("The current slot was not finished within {timeout:?}"))??;
+        }
+        Stopped::Paused => info!("The runner paused, stopping the rollup"),
+    }
+
+    info!("Waiting for the proofs of the processed slots");
+    let proofs_finished = shutdown.state.prover_queue.proofs_finished();
+    wait_for_step(proofs_finished, &mut stopped, signal.as_mut(), timeout)
+        .await
+        .with_context(|| format!("The processed slots were not proven within {timeout:?}"))?;
+
+    // The server is already stopped if it failed
+    let _ = rpc_server.stop();
+    rpc_server.stopped().await;
+
+    // Dropping the runner drops the prover service, which ends the proof publication, the
+    // storage manager and the ledger database of the runner
+    drop(run);
+    drop(runner);
+    shutdown.stop_database_readers().await;
+
+    info!("Publishing the remaining proofs");
+    let tasks_joined = shutdown.join_tasks();
+    wait_for_step(tasks_joined, &mut stopped, signal.as_mut(), timeout)
+        .await
+        .with_context(|| format!("The proofs were not published within {timeout:?}"))?;
+
+    info!("The rollup stopped, the databases are closed");
+    Ok(stopped)
+}
+
+/// Waits for `step`, for at most `timeout` on shutdown. On pause, waits as long as `step` takes,
+/// unless `signal` completes meanwhile: the pause becomes a shutdown, and `step` gets `timeout`.
+async fn wait_for_step(
+    step: impl Future<Output = ()>,
+    stopped: &mut Stopped,
+    signal: Pin<&mut impl Future<Output = ()>>,
+    timeout: Duration,
+) -> Result<(), tokio::time::error::Elapsed> {
+    let mut step = std::pin::pin!(step);
+    if *stopped == Stopped::Paused {
+        tokio::select! {
+            () = &mut step => return Ok(()),
+            () = signal => {
+                info!("Shutting down during the pause");
+                *stopped = Stopped::Shutdown;
+            }
+        }
+    }
+    tokio::time::timeout(timeout, step).await
+}
+
+/// Completes on SIGINT, or SIGTERM on unix.
+pub async fn termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigterm = signal(SignalKind::terminate()).expect("Failed to listen to SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = sigterm.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// A DA service that stops handing blocks to the runner once a shutdown is requested.
+#[derive(Clone)]
+pub struct GatedDaService<Da> {
+    inner: Da,
+    shutdown: Shutdown,
+}
+
+impl<Da> GatedDaService<Da> {
+    /// Wraps `inner`, gating its blocks with `shutdown`.
+    pub fn new(inner: Da, shutdown: Shutdown) -> Self {
+        Self { inner, shutdown }
+    }
+
+    /// Parks the caller forever.
+    async fn park<T>(&self) -> T {
+        self.shutdown.state.runner_parked.send_replace(true);
+        std::future::pending().await
+    }
+}
+
+#[async_trait]
+impl<Da> DaService for GatedDaService<Da>
+where
+    Da: DaService<Error = anyhow::Error> + Clone + Send + Sync,
+{
+    type Spec = Da::Spec;
+    type Verifier = Da::Verifier;
+    type FilteredBlock = Da::FilteredBlock;
+    type HeaderStream = Da::HeaderStream;
+    type Error = anyhow::Error;
+
+    /// Only the runner reads blocks by height, so it is parked here once the shutdown is
+    /// requested, between two slots.
+    async fn get_block_at(&self, height: u64) -> Result<Self::FilteredBlock, Self::Error> {
+        if self.shutdown.is_requested() || self.shutdown.pauses_before(height) {
+            return self.park().await;
+        }
+        tokio::select! {
+            block = self.inner.get_block_at(height) => block,
+            _ = self.shutdown.requested() => self.park().await,
+        }
+    }
+
+    async fn get_last_finalized_block_header(
+        &self,
+    ) -> Result<<Self::Spec as DaSpec>::BlockHeader, Self::Error> {
+        self.inner.get_last_finalized_block_header().await
+    }
+
+    async fn subscribe_finalized_header(&self) -> Result<Self::HeaderStream, Self::Error> {
+        self.inner.subscribe_finalized_header().await
+    }
+
+    async fn get_head_block_header(
+        &self,
+    ) -> Result<<Self::Spec as DaSpec>::BlockHeader, Self::Error> {
+        self.inner.get_head_block_header().await
+    }
+
+    fn extract_relevant_blobs(
+        &self,
+        block: &Self::FilteredBlock,
+    ) -> Vec<<Self::Spec as DaSpec>::BlobTransaction> {
+        self.inner.extract_relevant_blobs(block)
+    }
+
+    async fn get_extraction_proof(
+        &self,
+        block: &Self::FilteredBlock,
+        blobs: &[<Self::Spec as DaSpec>::BlobTransaction],
+    ) -> (
+        <Self::Spec as DaSpec>::InclusionMultiProof,
+        <Self::Spec as DaSpec>::CompletenessProof,
+    ) {
+        self.inner.get_extraction_proof(block, blobs).await
+    }
+
+    async fn send_transaction(&self, blob: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send_transaction(blob).await
+    }
+
+    async fn send_aggregated_zk_proof(
+        &self,
+        aggregated_proof_data: &[u8],
+    ) -> Result<u64, Self::Error> {
+        self.inner
+            .send_aggregated_zk_proof(aggregated_proof_data)
+            .await
+    }
+
+    async fn get_aggregated_proofs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.inner.get_aggregated_proofs_at(height).await
+    }
+}
@@ -0,0 +1,267 @@
+#![deny(missing_docs)]
+//! Snapshots of the storage of a node, to bootstrap new full nodes without replaying every DA
+//! block from `start_height`.
+//!
+//! A snapshot is a directory with a copy of the state and ledger databases of a stopped node,
+//! and a `manifest.json` with the height, the DA block and the state root they end at. The
+//! manifest is checked against the restored databases, and the state root against the
+//! `chain_history` module, before the node resumes from the height of the snapshot.
+//!
+//! Snapshots are written at the last height of the storage: the ledger database has no way to
+//! drop the slots after a given height, so the copy of a storage always ends at its head. A
+//! snapshot is restored to a directory next to the storage, which is renamed to the storage once
+//! it is verified, so a failed restore never touches the storage.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+use chain_history::{ChainHistory, SlotRecord};
+use serde::{Deserialize, Serialize};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{DaSpec, Spec, WorkingSet};
+use sov_state::Storage;
+use tracing::{info, warn};
+
+use crate::consistency::{check_storage, StorageCheck};
+use crate::export::open_storage;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const STORAGE_DIR: &str = "storage";
+
+/// The `manifest.json` of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// The height of the last slot in the snapshot.
+    pub height: u64,
+    /// The DA height of the last slot.
+    pub da_height: u64,
+    /// The hex-encoded hash of the DA block of the last slot.
+    pub da_block_hash: String,
+    /// The hex-encoded state root after the last slot.
+    pub state_root: String,
+    /// The `start_height` the snapshot was written with.
+    pub start_height: u64,
+}
+
+/// Writes a snapshot of the storage of a stopped node to `output`, at the last height of the
+/// storage.
+///
+/// Fails if `expected_height` is given and the storage ends at another height.
+pub fn create_snapshot<Da: DaSpec>(
+    storage_path: &Path,
+    start_height: u64,
+    expected_height: Option<u64>,
+    output: &Path,
+) -> anyhow::Result<SnapshotManifest> {
+    let height = match check_storage::<Da>(storage_path, start_height)? {
+        StorageCheck::Consistent { height } => height,
+        StorageCheck::Empty => bail!("The storage at {} is empty", storage_path.display()),
+        check => bail!(
+            "The storage at {} is inconsistent: {check:?}",
+            storage_path.display()
+        ),
+    };
+    if let Some(expected_height) = expected_height.filter(|expected| *expected != height) {
+        bail!(
+            "The storage at {} ends at height {height}, not {expected_height}: snapshots are \
+             written at the last height of the storage",
+            storage_path.display()
+        );
+    }
+    if !is_empty_dir(output)? {
+        bail!("The snapshot directory {} is not empty", output.display());
+    }
+
+    let manifest = {
+        let storage = open_storage::<Da>(storage_path)?;
+        let record = slot_record(&storage, height)?;
+        SnapshotManifest {
+            height,
+            da_height: record.da_height,
+            da_block_hash: hex::encode(record.da_block_hash),
+            // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+            state_root: hex::encode(storage.get_root_hash(height + 1)?),
+            start_height,
+        }
+    };
+
+    copy_dir(storage_path, &output.join(STORAGE_DIR))
+        .context("Failed to copy the storage to the snapshot")?;
+    fs::write(
+        output.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    info!(
+        "Wrote the snapshot of height {} to {}",
+        manifest.height,
+        output.display()
+    );
+    Ok(manifest)
+}
+
+/// Restores the snapshot in `input` to the empty storage of a node, then verifies it.
+///
+/// The snapshot is restored and verified next to the storage, which is only replaced once the
+/// snapshot verifies.
+pub fn restore_snapshot<Da: DaSpec>(
+    input: &Path,
+    storage_path: &Path,
+    start_height: u64,
+) -> anyhow::Result<SnapshotManifest> {
+    let manifest = read_manifest(input)?;
+    if manifest.start_height != start_height {
+        bail!(
+            "The snapshot was written with start_height {}, but the rollup config has \
+             start_height {start_height}",
+            manifest.start_height
+        );
+    }
+    if !is_empty_dir(storage_path)? {
+        bail!(
+            "The storage at {} is not empty, remove it to restore a snapshot",
+            storage_path.display()
+        );
+    }
+
+    let staging = staging_path(storage_path)?;
+    // Left over by an interrupted restore
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let restored = copy_dir(&input.join(STORAGE_DIR), &staging)
+        .context("Failed to copy the snapshot next to the storage")
+        .and_then(|()| {
+            verify_snapshot::<Da>(&manifest, &staging)
+                .context("The snapshot does not match its manifest")
+        });
+    if let Err(e) = restored {
+        fs::remove_dir_all(&staging)?;
+        return Err(e);
+    }
+
+    if storage_path.exists() {
+        // Only removes an empty directory
+        fs::remove_dir(storage_path)?;
+    }
+    fs::rename(&staging, storage_path).with_context(|| {
+        format!(
+            "Failed to move the restored snapshot from {} to {}",
+            staging.display(),
+            storage_path.display()
+        )
+    })?;
+
+    info!(
+        "Restored the snapshot of height {} to {}",
+        manifest.height,
+        storage_path.display()
+    );
+    Ok(manifest)
+}
+
+/// Restores the snapshot in `input` if the storage of the node is empty, so a new node resumes
+/// from the height of the snapshot.
+pub fn bootstrap_from_snapshot<Da: DaSpec>(
+    input: &Path,
+    storage_path: &Path,
+    start_height: u64,
+) -> anyhow::Result<()> {
+    if !is_empty_dir(storage_path)? {
+        warn!(
+            "The storage at {} is not empty, not restoring the snapshot {}",
+            storage_path.display(),
+            input.display()
+        );
+        return Ok(());
+    }
+    restore_snapshot::<Da>(input, storage_path, start_height)?;
+    Ok(())
+}
+
+/// Reads the manifest of the snapshot in `input`.
+pub fn read_manifest(input: &Path) -> anyhow::Result<SnapshotManifest> {
+    let path = input.join(MANIFEST_FILE);
+    let manifest =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&manifest).with_context(|| format!("Invalid {}", path.display()))
+}
+
+fn verify_snapshot<Da: DaSpec>(
+    manifest: &SnapshotManifest,
+    storage_path: &Path,
+) -> anyhow::Result<()> {
+    let height = match check_storage::<Da>(storage_path, manifest.start_height)? {
+        StorageCheck::Consistent { height } => height,
+        check => bail!("The restored storage is inconsistent: {check:?}"),
+    };
+    if height != manifest.height {
+        bail!(
+            "The restored storage ends at height {height}, not {}",
+            manifest.height
+        );
+    }
+
+    let storage = open_storage::<Da>(storage_path)?;
+    let record = slot_record(&storage, height)?;
+    if hex::encode(record.da_block_hash) != manifest.da_block_hash {
+        bail!("The DA block of height {height} does not match the manifest");
+    }
+    if hex::encode(storage.get_root_hash(height + 1)?) != manifest.state_root {
+        bail!("The state root of height {height} does not match the manifest");
+    }
+    // The chain history records the root each slot was applied to
+    if storage.get_root_hash(height)?.as_ref() != record.pre_state_root.as_slice() {
+        bail!("The state root before height {height} does not match the chain history");
+    }
+    Ok(())
+}
+
+fn slot_record(
+    storage: &<DefaultContext as Spec>::Storage,
+    height: u64,
+) -> anyhow::Result<SlotRecord> {
+    let mut working_set = WorkingSet::new(storage.clone());
+    ChainHistory::<DefaultContext>::default()
+        .get_slot(height, &mut working_set)
+        .with_context(|| format!("The chain history holds no record of height {height}"))
+}
+
+/// The directory next to the storage a snapshot is restored to, before it replaces the storage.
+fn staging_path(storage_path: &Path) -> anyhow::Result<PathBuf> {
+    let Some(name) = storage_path.file_name() else {
+        bail!("The storage path {} has no name", storage_path.display());
+    };
+    let mut staging_name = OsString::from(".");
+    staging_name.push(name);
+    staging_name.push(".restoring");
+    Ok(storage_path.with_file_name(staging_name))
+}
+
+fn is_empty_dir(path: &Path) -> anyhow::Result<bool> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    Ok(fs::read_dir(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .next()
+        .is_none())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(to)?;
+    let entries =
+        fs::read_dir(from).with_context(|| format!("Failed to read {}", from.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
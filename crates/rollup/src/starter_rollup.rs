@@ -0,0 +1,352 @@
+#![deny(missing_docs)]
+//! StarterRollup provides a minimal self-contained rollup implementation, generic over the DA layer
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use sov_db::ledger_db::LedgerDB;
+use sov_modules_api::default_context::{DefaultContext, ZkDefaultContext};
+use sov_modules_api::{Address, DaSpec, Spec};
+use sov_modules_rollup_blueprint::{RollupBlueprint, WalletBlueprint};
+use sov_modules_stf_blueprint::kernels::basic::BasicKernel;
+use sov_modules_stf_blueprint::StfBlueprint;
+use sov_prover_storage_manager::ProverStorageManager;
+use sov_risc0_adapter::host::Risc0Host;
+use sov_risc0_adapter::Risc0MethodId;
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_interface::zk::ZkvmHost;
+use sov_state::config::Config as StorageConfig;
+use sov_state::Storage;
+use sov_state::{DefaultStorageSpec, ZkStorage};
+use sov_stf_runner::ParallelProverService;
+use sov_stf_runner::RollupConfig;
+use sov_stf_runner::RollupProverConfig;
+use stf_starter::Runtime;
+use tokio::sync::mpsc;
+
+use crate::metrics::{collect_slot_metrics, ProverQueue};
+use crate::proofs::{LastVerifiedProof, ProofFollower, ProofPublisher, ProofTap, ProvenSlotRecord};
+use crate::prover::ProverMode;
+use crate::sequencer::CheckedSequencerConfig;
+use crate::shutdown::{GatedDaService, Shutdown};
+use crate::status::{HealthConfig, NodeStatus};
+
+/// Everything the [`StarterRollup`] needs to know about a DA layer.
+///
+/// Adding a new DA backend to the rollup only requires implementing this trait, and building
+/// a guest for it in the `risc0-starter` crate.
+#[async_trait]
+pub trait DaAdapter: Send + Sync + 'static {
+    /// The DA service the rollup reads blocks from and submits batches to.
+    type DaService: DaService<Spec = Self::DaSpec, Error = anyhow::Error> + Clone + Send + Sync;
+    /// The specification of the DA layer.
+    type DaSpec: DaSpec;
+    /// The `[da]` section of the rollup config.
+    type DaConfig: DeserializeOwned + Send + Sync;
+
+    /// The guest code proving the rollup on this DA layer.
+    const GUEST_ELF: &'static [u8];
+    /// The image id of [`Self::GUEST_ELF`], which the proofs are verified against.
+    const GUEST_ID: [u32; 8];
+
+    /// Creates the DA service.
+    async fn create_da_service(
+        rollup_config: &RollupConfig<Self::DaConfig>,
+    ) -> anyhow::Result<Self::DaService>;
+
+    /// Creates the verifier the prover uses to check DA blocks.
+    fn create_da_verifier() -> <Self::DaService as DaService>::Verifier;
+
+    /// Checks that the guest was built with the same DA parameters as the node.
+    fn check_guest() -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Checks that the node can submit batches from the DA address of its sequencer. Fails on
+    /// the DA layers that cannot check it, rather than running a sequencer with the wrong key.
+    async fn check_sequencer(
+        _da_config: &Self::DaConfig,
+        sequencer_da_address: &<Self::DaSpec as DaSpec>::Address,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "Checking the DA address {} of the sequencer is not supported on this DA layer",
+            sequencer_da_address
+        )
+    }
+}
+
+/// Rollup on the DA layer described by a [`DaAdapter`].
+pub struct StarterRollup<Da: DaAdapter> {
+    /// The sequencer this node runs as, if any.
+    pub sequencer: Option<CheckedSequencerConfig<<Da::DaSpec as DaSpec>::Address>>,
+    /// The thresholds of the sync status, and where to serve the health endpoints, if anywhere.
+    pub health: Option<HealthConfig>,
+    /// The signers of the `eth_*` methods. Without them, the node does not serve the methods.
+    #[cfg(feature = "experimental")]
+    pub eth: Option<crate::eth::EthConfig>,
+    prover_queue: ProverQueue,
+    last_verified_proof: LastVerifiedProof,
+    /// The first slot the prover of the last created rollup proves.
+    first_prover_slot: Arc<AtomicU64>,
+    shutdown: Shutdown,
+    da_service: Option<Da::DaService>,
+    phantom: PhantomData<Da>,
+}
+
+impl<Da: DaAdapter> StarterRollup<Da> {
+    /// Creates a rollup running as the given sequencer, if any.
+    pub fn new(sequencer: Option<CheckedSequencerConfig<<Da::DaSpec as DaSpec>::Address>>) -> Self {
+        let prover_queue = ProverQueue::default();
+        Self {
+            sequencer,
+            health: None,
+            #[cfg(feature = "experimental")]
+            eth: None,
+            shutdown: Shutdown::new(prover_queue.clone()),
+            prover_queue,
+            last_verified_proof: LastVerifiedProof::default(),
+            first_prover_slot: Arc::new(AtomicU64::new(1)),
+            da_service: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Opens the DA service the rollup reads blocks from, which must be done before the rollup
+    /// is created, so the failures to reach the DA layer are reported rather than panicking.
+    pub async fn open_da_service(
+        &mut self,
+        rollup_config: &RollupConfig<Da::DaConfig>,
+    ) -> anyhow::Result<()> {
+        self.da_service = Some(Da::create_da_service(rollup_config).await?);
+        Ok(())
+    }
+
+    fn opened_da_service(&self) -> anyhow::Result<Da::DaService> {
+        self.da_service
+            .clone()
+            .context("The DA service must be opened with `open_da_service` first")
+    }
+
+    /// The [`Shutdown`] of the rollups this blueprint creates, to pass to
+    /// [`run_until_shutdown`](crate::shutdown::run_until_shutdown).
+    pub fn shutdown(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+}
+
+impl<Da: DaAdapter> StarterRollup<Da> {
+    /// Checks that the guest of the DA layer can be used in the given prover mode.
+    pub fn check_guest(prover_mode: ProverMode) -> anyhow::Result<()> {
+        prover_mode.check_guest_elf(Da::GUEST_ELF)?;
+        // The guests are built without the `experimental` feature, so their runtime has no EVM
+        #[cfg(feature = "experimental")]
+        if matches!(prover_mode, ProverMode::Execute | ProverMode::Prove) {
+            anyhow::bail!(
+                "The guest runtime does not include the `evm` module, run the node with \
+                `--prover-mode skip` or `simulate` when the `experimental` feature is enabled"
+            );
+        }
+        Da::check_guest()
+    }
+
+    /// Creates a [`ProofFollower`] verifying the proofs posted to the DA layer since the
+    /// rollup start height, on the DA service opened by [`Self::open_da_service`]. The last
+    /// proof it verifies is reported by `node_syncStatus`.
+    pub fn create_proof_follower(
+        &self,
+        rollup_config: &RollupConfig<Da::DaConfig>,
+    ) -> anyhow::Result<ProofFollower<Da::DaService, Risc0Host<'static>>> {
+        Ok(ProofFollower::new(
+            self.opened_da_service()?,
+            Risc0MethodId::new(Da::GUEST_ID),
+            rollup_config.runner.start_height,
+            self.last_verified_proof.clone(),
+        ))
+    }
+}
+
+impl<Da: DaAdapter> Default for StarterRollup<Da> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// This is the place, where all the rollup components come together and
+/// they can be easily swapped with alternative implementations as needed.
+#[async_trait]
+impl<Da: DaAdapter> RollupBlueprint for StarterRollup<Da> {
+    /// This component defines the Data Availability layer, which the runner stops reading
+    /// blocks from on shutdown.
+    type DaService = GatedDaService<Da::DaService>;
+    type DaSpec = Da::DaSpec;
+    type DaConfig = Da::DaConfig;
+
+    /// The concrete ZkVm used in the rollup, publishing its proofs to the DA layer.
+    type Vm = ProofTap<Risc0Host<'static>>;
+
+    /// Context for the Zero Knowledge environment.
+    type ZkContext = ZkDefaultContext;
+    /// Context for the ZNative environment.
+    type NativeContext = DefaultContext;
+
+    /// Manager for the native storage lifecycle.
+    type StorageManager = ProverStorageManager<Self::DaSpec, DefaultStorageSpec>;
+
+    /// Runtime for the Zero Knowledge environment.
+    type ZkRuntime = Runtime<Self::ZkContext, Self::DaSpec>;
+    /// Runtime for the Native environment.
+    type NativeRuntime = Runtime<Self::NativeContext, Self::DaSpec>;
+
+    /// Kernels.
+    type NativeKernel = BasicKernel<Self::NativeContext, Self::DaSpec>;
+    type ZkKernel = BasicKernel<Self::ZkContext, Self::DaSpec>;
+
+    /// Prover service.
+    type ProverService = ParallelProverService<
+        <<Self::NativeContext as Spec>::Storage as Storage>::Root,
+        <<Self::NativeContext as Spec>::Storage as Storage>::Witness,
+        Self::DaService,
+        Self::Vm,
+        StfBlueprint<
+            Self::ZkContext,
+            Self::DaSpec,
+            <Self::Vm as ZkvmHost>::Guest,
+            Self::ZkRuntime,
+            Self::ZkKernel,
+        >,
+    >;
+
+    /// This function generates RPC methods for the rollup, allowing for extension with custom endpoints.
+    fn create_rpc_methods(
+        &self,
+        storage: &<Self::NativeContext as Spec>::Storage,
+        ledger_db: &LedgerDB,
+        da_service: &Self::DaService,
+    ) -> Result<jsonrpsee::RpcModule<()>, anyhow::Error> {
+        // The prover proves the slots the runner processes, from the one after the ledger head
+        let head_slot = ledger_db
+            .get_head_slot()?
+            .map(|(slot_number, _)| slot_number.0)
+            .unwrap_or_default();
+        self.first_prover_slot
+            .store(head_slot + 1, Ordering::SeqCst);
+
+        let sequencer = match &self.sequencer {
+            Some(sequencer) => {
+                sequencer.check_registered::<Self::DaSpec>(storage, ledger_db)?;
+                sequencer.rollup_address()
+            }
+            // Nodes that are not sequencers still expose the sequencer RPC, with a placeholder address.
+            None => Address::new([0; 32]),
+        };
+
+        let mut rpc_methods = sov_modules_rollup_blueprint::register_rpc::<
+            Self::NativeRuntime,
+            Self::NativeContext,
+            Self::DaService,
+        >(storage, ledger_db, da_service, sequencer)?;
+
+        #[cfg(feature = "experimental")]
+        match &self.eth {
+            Some(eth) => crate::eth::register_ethereum::<Self::DaService>(
+                da_service.clone(),
+                storage.clone(),
+                eth,
+                &mut rpc_methods,
+            )?,
+            None => tracing::warn!(
+                "The rollup config has no `[eth]` section, the `eth_*` methods are not served"
+            ),
+        }
+
+        let status = Arc::new(NodeStatus::new(
+            storage.clone(),
+            ledger_db.clone(),
+            da_service.clone(),
+            self.prover_queue.clone(),
+            self.last_verified_proof.clone(),
+            self.health.as_ref(),
+        ));
+        status.register_rpc_methods(&mut rpc_methods)?;
+        if let Some(health) = &self.health {
+            self.shutdown
+                .track_database_reader(status.serve(health.bind_address()?)?);
+        }
+
+        self.shutdown
+            .track_database_reader(tokio::spawn(collect_slot_metrics::<Self::DaSpec>(
+                ledger_db.clone(),
+                storage.clone(),
+                self.prover_queue.clone(),
+            )));
+        Ok(rpc_methods)
+    }
+
+    async fn create_da_service(
+        &self,
+        _rollup_config: &RollupConfig<Self::DaConfig>,
+    ) -> Self::DaService {
+        let da_service = self
+            .opened_da_service()
+            .expect("The DA service is opened before the rollup is created");
+        GatedDaService::new(da_service, self.shutdown.clone())
+    }
+
+    async fn create_prover_service(
+        &self,
+        prover_config: RollupProverConfig,
+        rollup_config: &RollupConfig<Self::DaConfig>,
+        da_service: &Self::DaService,
+    ) -> Self::ProverService {
+        let (proofs_sender, proofs_receiver) = mpsc::unbounded_channel();
+        self.shutdown.track(tokio::spawn(
+            ProofPublisher::new(
+                da_service.clone(),
+                rollup_config.prover_service.aggregated_proof_block_jump,
+                proofs_receiver,
+                Some(ProvenSlotRecord {
+                    storage_path: rollup_config.storage.path.clone(),
+                    first_slot: self.first_prover_slot.clone(),
+                }),
+            )
+            .run(),
+        ));
+
+        if !matches!(prover_config, RollupProverConfig::Skip) {
+            self.prover_queue.enable();
+        }
+        let vm = ProofTap::new(
+            Risc0Host::new(Da::GUEST_ELF),
+            proofs_sender,
+            self.prover_queue.clone(),
+        );
+        let zk_stf = StfBlueprint::new();
+        let zk_storage = ZkStorage::new();
+        let da_verifier = Da::create_da_verifier();
+
+        ParallelProverService::new_with_default_workers(
+            vm,
+            zk_stf,
+            da_verifier,
+            prover_config,
+            zk_storage,
+            rollup_config.prover_service,
+        )
+    }
+
+    fn create_storage_manager(
+        &self,
+        rollup_config: &RollupConfig<Self::DaConfig>,
+    ) -> anyhow::Result<Self::StorageManager> {
+        let storage_config = StorageConfig {
+            path: rollup_config.storage.path.clone(),
+        };
+        ProverStorageManager::new(storage_config)
+    }
+}
+
+impl<Da: DaAdapter> WalletBlueprint for StarterRollup<Da> {}
@@ -0,0 +1,285 @@
+#![deny(missing_docs)]
+//! Sync status of the node, for orchestrators: the `node_syncStatus` RPC method, and the
+//! `/health` and `/ready` HTTP endpoints configured by the `[health]` section of the rollup
+//! config.
+//!
+//! A node is ready once it has caught up with the head of the DA layer, within `max_lag` DA
+//! blocks. It is unhealthy when it is behind and has not processed a slot for
+//! `stall_timeout_secs`, or when its storage or DA layer cannot be read.
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use chain_history::ChainHistory;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Response, StatusCode};
+use jsonrpsee::RpcModule;
+use serde::{Deserialize, Serialize};
+use sov_db::ledger_db::LedgerDB;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{Spec, WorkingSet};
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::DaService;
+use sov_state::Storage;
+use sov_stf_runner::from_toml_path;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::metrics::ProverQueue;
+use crate::proofs::{LastVerifiedProof, VerifiedProof};
+
+/// The default lag, in DA blocks, under which a node is ready.
+pub const DEFAULT_MAX_LAG: u64 = 2;
+/// The default time without a processed slot after which a node that is behind is stalled.
+pub const DEFAULT_STALL_TIMEOUT_SECS: u64 = 60;
+
+/// The `[health]` section of the rollup config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// The host to serve the `/health` and `/ready` endpoints on.
+    pub bind_host: String,
+    /// The port to serve the `/health` and `/ready` endpoints on.
+    pub bind_port: u16,
+    /// The lag, in DA blocks, under which the node is ready.
+    #[serde(default = "default_max_lag")]
+    pub max_lag: u64,
+    /// The time without a processed slot after which a node that is behind is stalled.
+    #[serde(default = "default_stall_timeout_secs")]
+    pub stall_timeout_secs: u64,
+}
+
+fn default_max_lag() -> u64 {
+    DEFAULT_MAX_LAG
+}
+
+fn default_stall_timeout_secs() -> u64 {
+    DEFAULT_STALL_TIMEOUT_SECS
+}
+
+#[derive(Deserialize)]
+struct HealthSection {
+    #[serde(default)]
+    health: Option<HealthConfig>,
+}
+
+impl HealthConfig {
+    /// Reads the `[health]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: HealthSection =
+            from_toml_path(path).context("Failed to read the `[health]` section")?;
+        Ok(section.health)
+    }
+
+    /// The address to serve the endpoints on.
+    pub fn bind_address(&self) -> anyhow::Result<SocketAddr> {
+        let bind_host: IpAddr = self
+            .bind_host
+            .parse()
+            .with_context(|| format!("Invalid health bind host {:?}", self.bind_host))?;
+        Ok(SocketAddr::new(bind_host, self.bind_port))
+    }
+}
+
+/// The response of the `node_syncStatus` RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStatus {
+    /// The rollup height of the last processed slot.
+    pub rollup_height: u64,
+    /// The DA height of the last processed slot, if a slot was processed.
+    pub last_processed_da_height: Option<u64>,
+    /// The height of the head of the DA layer.
+    pub da_head_height: u64,
+    /// The number of DA blocks the node is behind the head of the DA layer.
+    pub lag: u64,
+    /// The hex-encoded state root after the last processed slot.
+    pub state_root: Option<String>,
+    /// The processed slots the prover has not started to prove.
+    pub prover_backlog: u64,
+    /// The last proof posted to the DA layer that the node verified, if it runs with
+    /// `--verify-da-proofs`.
+    pub last_verified_proof: Option<VerifiedProof>,
+    /// Whether the node is within the configured lag of the DA head.
+    pub ready: bool,
+    /// Whether the node is behind and has not processed a slot for the stall timeout.
+    pub stalled: bool,
+}
+
+/// The height the node last made progress at.
+struct Progress {
+    rollup_height: u64,
+    since: Instant,
+}
+
+/// Computes the [`SyncStatus`] of the node.
+pub struct NodeStatus<Da: DaService> {
+    storage: <DefaultContext as Spec>::Storage,
+    ledger_db: LedgerDB,
+    da_service: Da,
+    prover_queue: ProverQueue,
+    last_verified_proof: LastVerifiedProof,
+    max_lag: u64,
+    stall_timeout: Duration,
+    progress: Mutex<Progress>,
+}
+
+impl<Da: DaService<Error = anyhow::Error>> NodeStatus<Da> {
+    /// Creates the status of a node, with the thresholds of `health_config` or the defaults.
+    pub fn new(
+        storage: <DefaultContext as Spec>::Storage,
+        ledger_db: LedgerDB,
+        da_service: Da,
+        prover_queue: ProverQueue,
+        last_verified_proof: LastVerifiedProof,
+        health_config: Option<&HealthConfig>,
+    ) -> Self {
+        let (max_lag, stall_timeout_secs) = match health_config {
+            Some(config) => (config.max_lag, config.stall_timeout_secs),
+            None => (DEFAULT_MAX_LAG, DEFAULT_STALL_TIMEOUT_SECS),
+        };
+        Self {
+            storage,
+            ledger_db,
+            da_service,
+            prover_queue,
+            last_verified_proof,
+            max_lag,
+            stall_timeout: Duration::from_secs(stall_timeout_secs),
+            progress: Mutex::new(Progress {
+                rollup_height: 0,
+                since: Instant::now(),
+            }),
+        }
+    }
+
+    /// Computes the sync status of the node.
+    pub async fn sync_status(&self) -> anyhow::Result<SyncStatus> {
+        let mut working_set = WorkingSet::new(self.storage.clone());
+        let chain_history = ChainHistory::<DefaultContext>::default();
+        let rollup_height = chain_history.get_current_height(&mut working_set);
+        let last_processed_da_height = chain_history
+            .get_slot(rollup_height, &mut working_set)
+            .map(|slot| slot.da_height);
+
+        // The state after slot `N` is stored as version `N + 1`, genesis being version 1
+        let state_root = match self.ledger_db.get_head_slot()? {
+            Some((slot_number, _)) => {
+                Some(hex::encode(self.storage.get_root_hash(slot_number.0 + 1)?))
+            }
+            None => None,
+        };
+
+        let da_head_height = self
+            .da_service
+            .get_head_block_header()
+            .await
+            .context("Failed to read the head of the DA layer")?
+            .height();
+        let lag = da_head_height.saturating_sub(last_processed_da_height.unwrap_or_default());
+
+        let stalled = {
+            let mut progress = self.progress.lock().expect("Status lock poisoned");
+            if rollup_height != progress.rollup_height {
+                *progress = Progress {
+                    rollup_height,
+                    since: Instant::now(),
+                };
+            }
+            lag > self.max_lag && progress.since.elapsed() > self.stall_timeout
+        };
+
+        Ok(SyncStatus {
+            rollup_height,
+            last_processed_da_height,
+            da_head_height,
+            lag,
+            state_root,
+            prover_backlog: self.prover_queue.depth(),
+            last_verified_proof: self.last_verified_proof.get(),
+            ready: lag <= self.max_lag,
+            stalled,
+        })
+    }
+}
+
+impl<Da> NodeStatus<Da>
+where
+    Da: DaService<Error = anyhow::Error> + Send + Sync + 'static,
+{
+    /// Registers the `node_syncStatus` RPC method.
+    pub fn register_rpc_methods(
+        self: &Arc<Self>,
+        rpc_methods: &mut RpcModule<()>,
+    ) -> anyhow::Result<()> {
+        let status = self.clone();
+        rpc_methods.register_async_method("node_syncStatus", move |_, _| {
+            let status = status.clone();
+            async move {
+                status
+                    .sync_status()
+                    .await
+                    .map_err(|e| jsonrpsee::core::Error::Custom(format!("{e:?}")))
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Serves the `/health` and `/ready` endpoints on `address`, until the returned task is
+    /// aborted.
+    pub fn serve(self: &Arc<Self>, address: SocketAddr) -> anyhow::Result<JoinHandle<()>> {
+        let server = hyper::Server::try_bind(&address)
+            .with_context(|| format!("Failed to bind the health endpoints to {address}"))?;
+
+        let status = self.clone();
+        let make_service = make_service_fn(move |_| {
+            let status = status.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let status = status.clone();
+                    async move { Ok::<_, Infallible>(status.respond(request.uri().path()).await) }
+                }))
+            }
+        });
+
+        info!("Serving the health endpoints on http://{address}");
+        Ok(tokio::spawn(async move {
+            if let Err(e) = server.serve(make_service).await {
+                warn!("The health endpoints stopped: {:?}", e);
+            }
+        }))
+    }
+
+    async fn respond(&self, path: &str) -> Response<Body> {
+        let check: fn(&SyncStatus) -> bool = match path {
+            "/health" => |status: &SyncStatus| !status.stalled,
+            "/ready" => |status: &SyncStatus| status.ready,
+            _ => return response(StatusCode::NOT_FOUND, Body::empty()),
+        };
+
+        match self.sync_status().await {
+            Ok(status) => {
+                let code = if check(&status) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                let body =
+                    serde_json::to_string(&status).expect("Status serialization cannot fail");
+                response(code, Body::from(body))
+            }
+            Err(e) => response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                Body::from(format!("{e:?}")),
+            ),
+        }
+    }
+}
+
+fn response(code: StatusCode, body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    *response.status_mut() = code;
+    response
+}
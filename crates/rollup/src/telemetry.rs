@@ -0,0 +1,166 @@
+#![deny(missing_docs)]
+//! Logging of the node, configured by the `[telemetry]` section of the rollup config.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use sov_stf_runner::from_toml_path;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+
+/// The log level of a node without a `[telemetry]` section or `RUST_LOG`.
+const DEFAULT_LOG_LEVEL: &str = "info,hyper=info";
+
+/// How log lines are formatted.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines.
+    #[default]
+    Text,
+    /// A JSON object per line, with the fields of the current spans.
+    Json,
+}
+
+/// How often the log file is rotated.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    /// A new file every minute.
+    Minutely,
+    /// A new file every hour.
+    Hourly,
+    /// A new file every day.
+    #[default]
+    Daily,
+    /// A single file.
+    Never,
+}
+
+impl From<LogRotation> for Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        match rotation {
+            LogRotation::Minutely => Rotation::MINUTELY,
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        }
+    }
+}
+
+/// Logging to rolling files, in addition to stdout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFileConfig {
+    /// The directory of the log files.
+    pub directory: PathBuf,
+    /// The name of the log files, suffixed with their date when they are rotated.
+    #[serde(default = "default_file_name")]
+    pub file_name: String,
+    /// How often the log file is rotated.
+    #[serde(default)]
+    pub rotation: LogRotation,
+}
+
+fn default_file_name() -> String {
+    "node.log".to_owned()
+}
+
+/// The `[telemetry]` section of the rollup config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// The log filter, in the `RUST_LOG` syntax (e.g. `info,sov_sequencer=debug`). The
+    /// `RUST_LOG` environment variable takes precedence over it.
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// How log lines are formatted.
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// Also writes the logs to rolling files.
+    #[serde(default)]
+    pub log_file: Option<LogFileConfig>,
+}
+
+fn default_log_level() -> String {
+    DEFAULT_LOG_LEVEL.to_owned()
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            log_level: default_log_level(),
+            log_format: LogFormat::default(),
+            log_file: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TelemetrySection {
+    #[serde(default)]
+    telemetry: Option<TelemetryConfig>,
+}
+
+impl TelemetryConfig {
+    /// Reads the `[telemetry]` section of the rollup config, if there is one.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let section: TelemetrySection =
+            from_toml_path(path).context("Failed to read the `[telemetry]` section")?;
+        Ok(section.telemetry)
+    }
+
+    /// Installs the global logger. The returned guard flushes the log file when dropped, so it
+    /// must be kept until the node exits.
+    pub fn init_logging(&self) -> anyhow::Result<Option<WorkerGuard>> {
+        let filter = match std::env::var(EnvFilter::DEFAULT_ENV) {
+            Ok(directives) => EnvFilter::try_new(directives)
+                .with_context(|| format!("Invalid `{}` log filter", EnvFilter::DEFAULT_ENV))?,
+            Err(_) => EnvFilter::try_new(&self.log_level)
+                .with_context(|| format!("Invalid log level {:?}", self.log_level))?,
+        };
+
+        let (file_layer, guard) = match &self.log_file {
+            Some(log_file) => {
+                let appender = RollingFileAppender::new(
+                    log_file.rotation.into(),
+                    &log_file.directory,
+                    &log_file.file_name,
+                );
+                let (writer, guard) = tracing_appender::non_blocking(appender);
+                (Some(self.log_format.layer(writer, false)), Some(guard))
+            }
+            None => (None, None),
+        };
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(self.log_format.layer(std::io::stdout, true))
+            .with(file_layer)
+            .try_init()
+            .context("Failed to install the logger")?;
+        Ok(guard)
+    }
+}
+
+impl LogFormat {
+    fn layer<S, W>(self, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let layer = fmt::layer().with_writer(writer).with_ansi(ansi);
+        match self {
+            LogFormat::Text => layer.boxed(),
+            LogFormat::Json => layer
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .boxed(),
+        }
+    }
+}
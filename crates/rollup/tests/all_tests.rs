@@ -1,3 +1,18 @@
 mod bank;
+mod chain_history;
+mod consistency;
+mod export;
+mod fees;
+mod genesis;
+mod indexer;
+mod metrics;
+mod mock_da;
+mod proofs;
+mod pruning;
+mod restart;
+mod rewards;
+mod slashing;
+mod snapshot;
+mod status;
 // Add additional tests here
 mod test_helpers;
@@ -0,0 +1,64 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use super::test_helpers::{copy_mock_genesis, send_create_token, update_json, with_rollup};
+use chain_history::ChainHistoryRpcClient;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_sequencer::utils::SimpleClient;
+use stf_starter::genesis_config::GenesisPaths;
+
+const WINDOW_SIZE: u64 = 2;
+
+#[tokio::test]
+async fn chain_history_tests() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    write_genesis_with_window_size(genesis_dir.path())?;
+
+    with_rollup(
+        GenesisPaths::from_dir(genesis_dir.path()),
+        check_chain_history,
+    )
+    .await
+}
+
+fn write_genesis_with_window_size(dir: &Path) -> Result<(), anyhow::Error> {
+    copy_mock_genesis(dir)?;
+    update_json(&dir.join("chain_history.json"), |chain_history| {
+        chain_history["window_size"] = WINDOW_SIZE.into();
+    })
+}
+
+async fn check_chain_history(rpc_address: SocketAddr) -> Result<(), anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    let key = DefaultPrivateKey::generate();
+
+    // Every transaction is posted in its own mock DA block
+    for nonce in 0..=WINDOW_SIZE {
+        send_create_token(rpc_address, &key, nonce).await?;
+    }
+
+    let latest = ChainHistoryRpcClient::<DefaultContext>::latest_slot(client.http()).await?;
+    assert!(latest.height > WINDOW_SIZE);
+    let latest_slot = latest.slot.expect("The latest slot must be recorded");
+
+    let previous =
+        ChainHistoryRpcClient::<DefaultContext>::slot(client.http(), latest.height - 1).await?;
+    let previous_slot = previous.slot.expect("The previous slot is in the window");
+    assert_eq!(previous_slot.da_height + 1, latest_slot.da_height);
+    assert_ne!(previous_slot.da_block_hash, latest_slot.da_block_hash);
+    // The previous slot created a token, so the state root changed
+    assert_ne!(previous_slot.pre_state_root, latest_slot.pre_state_root);
+
+    let evicted =
+        ChainHistoryRpcClient::<DefaultContext>::slot(client.http(), latest.height - WINDOW_SIZE)
+            .await?;
+    assert_eq!(evicted.slot, None);
+
+    let window = ChainHistoryRpcClient::<DefaultContext>::window(client.http()).await?;
+    assert_eq!(window.window_size, WINDOW_SIZE);
+    assert_eq!(window.current_height, latest.height);
+    assert_eq!(window.first_height, Some(latest.height - WINDOW_SIZE + 1));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use super::test_helpers::{send_create_token, Node};
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_rollup_starter::consistency::{check_storage, verify_storage, StorageCheck};
+
+#[tokio::test]
+async fn consistency_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let key = DefaultPrivateKey::generate();
+
+    assert_eq!(
+        check_storage::<MockDaSpec>(storage_dir.path(), 1)?,
+        StorageCheck::Empty
+    );
+
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let height = send_create_token(node.rpc_address, &key, 0).await?;
+    node.stop().await?;
+
+    // The test node starts at DA height 1
+    assert_eq!(
+        check_storage::<MockDaSpec>(storage_dir.path(), 1)?,
+        StorageCheck::Consistent { height }
+    );
+    assert_eq!(
+        check_storage::<MockDaSpec>(storage_dir.path(), 3)?,
+        StorageCheck::StartHeightMismatch {
+            height,
+            configured: 3,
+            stored: 1,
+        }
+    );
+
+    assert!(verify_storage::<MockDaSpec>(storage_dir.path(), 3, false).is_err());
+    assert_eq!(
+        verify_storage::<MockDaSpec>(storage_dir.path(), 3, true)?,
+        1
+    );
+    assert_eq!(
+        verify_storage::<MockDaSpec>(storage_dir.path(), 1, false)?,
+        1
+    );
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use super::test_helpers::{deployer_key, signed_tx, start_rollup_with_storage, subscribe_slots};
+use sov_bank::{BankConfig, Coins};
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::{PrivateKey, Spec};
+use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisPaths;
+use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
+use sov_rollup_starter::export::{export_state, open_storage};
+use sov_rollup_starter::genesis::{read_genesis_sections, write_genesis_sections};
+use sov_sequencer::utils::SimpleClient;
+use sov_stf_runner::RollupProverConfig;
+use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::{Runtime, RuntimeCall};
+
+const GENESIS_DIR: &str = "../../test-data/genesis/mock/";
+const CHAIN_STATE_PATH: &str = "../../test-data/genesis/mock/chain_state.json";
+const GAS_TOKEN_NAME: &str = "sov-gas-token";
+const LOCK_TOKEN_NAME: &str = "sov-demo-token";
+/// The balance of the deployer in both tokens of the mock `bank.json`.
+const DEPLOYER_BALANCE: u64 = 100000000;
+const TRANSFER_AMOUNT: u64 = 1000;
+
+#[tokio::test]
+async fn export_state_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let storage_path = storage_dir.path().to_path_buf();
+    let recipient = DefaultPrivateKey::generate().to_address();
+
+    let (port_tx, port_rx) = tokio::sync::oneshot::channel();
+    let rollup_task = tokio::spawn(async move {
+        start_rollup_with_storage(
+            port_tx,
+            GenesisPaths::from_dir(GENESIS_DIR),
+            BasicKernelGenesisPaths {
+                chain_state: CHAIN_STATE_PATH.into(),
+            },
+            RollupProverConfig::Execute,
+            &storage_path,
+        )
+        .await;
+    });
+
+    let port = port_rx.await.unwrap();
+    let height = send_transfer(port, recipient).await?;
+
+    rollup_task.abort();
+    let _ = rollup_task.await;
+
+    let storage = open_storage_of_stopped_rollup(storage_dir.path()).await?;
+    let deployer = deployer_key()?.to_address::<<DefaultContext as Spec>::Address>();
+    let template = read_genesis_sections(
+        &GenesisPaths::from_dir(GENESIS_DIR),
+        Some(Path::new(CHAIN_STATE_PATH)),
+    )?;
+    // The state at the previous height is the one before the transfer
+    let before = export_state::<MockDaSpec>(&storage, height - 1, &template, false)?;
+    let bank_before: BankConfig<DefaultContext> = serde_json::from_value(before["bank"].clone())?;
+    let gas_token_before = bank_before
+        .tokens
+        .iter()
+        .find(|token| token.token_name == GAS_TOKEN_NAME)
+        .expect("The gas token is exported");
+    assert!(gas_token_before
+        .address_and_balances
+        .contains(&(deployer, DEPLOYER_BALANCE)));
+    assert!(!gas_token_before
+        .address_and_balances
+        .iter()
+        .any(|(address, _)| *address == recipient));
+
+    // The deployer sent a transaction, and the genesis cannot set its nonce
+    let error = export_state::<MockDaSpec>(&storage, height, &template, false)
+        .expect_err("The export fails without resetting the nonces");
+    assert!(error.to_string().contains("nonces"));
+    let sections = export_state::<MockDaSpec>(&storage, height, &template, true)?;
+
+    let bank: BankConfig<DefaultContext> = serde_json::from_value(sections["bank"].clone())?;
+    let exported_token = |name: &str| {
+        bank.tokens
+            .iter()
+            .find(|token| token.token_name == name)
+            .unwrap_or_else(|| panic!("The token `{name}` is exported"))
+    };
+    let gas_token = exported_token(GAS_TOKEN_NAME);
+    assert!(gas_token
+        .address_and_balances
+        .contains(&(recipient, TRANSFER_AMOUNT)));
+    assert!(gas_token
+        .address_and_balances
+        .contains(&(deployer, DEPLOYER_BALANCE - TRANSFER_AMOUNT)));
+    // The coins locked by the sequencer are credited back
+    assert!(exported_token(LOCK_TOKEN_NAME)
+        .address_and_balances
+        .contains(&(deployer, DEPLOYER_BALANCE)));
+
+    // The exported genesis boots a new chain
+    let genesis_dir = tempfile::tempdir()?;
+    write_genesis_sections(&sections, genesis_dir.path())?;
+    Runtime::<DefaultContext, MockDaSpec>::genesis_config(&GenesisPaths::from_dir(
+        genesis_dir.path(),
+    ))?;
+    Ok(())
+}
+
+/// The storage is released once the tasks of the aborted rollup have been dropped.
+async fn open_storage_of_stopped_rollup(
+    path: &Path,
+) -> Result<<DefaultContext as Spec>::Storage, anyhow::Error> {
+    let mut attempts = 0;
+    loop {
+        match open_storage::<MockDaSpec>(path) {
+            Ok(storage) => return Ok(storage),
+            Err(_) if attempts < 50 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Sends gas tokens from the deployer to `recipient`, returning the height they were sent at.
+async fn send_transfer(
+    rpc_address: SocketAddr,
+    recipient: <DefaultContext as Spec>::Address,
+) -> Result<u64, anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    let mut slot_processed_subscription = subscribe_slots(&client).await?;
+
+    let msg = RuntimeCall::bank(sov_bank::CallMessage::Transfer {
+        to: recipient,
+        coins: Coins {
+            amount: TRANSFER_AMOUNT,
+            token_address: sov_bank::get_genesis_token_address::<DefaultContext>(GAS_TOKEN_NAME, 0),
+        },
+    });
+    let nonce = 0;
+    client
+        .send_transaction(signed_tx(&deployer_key()?, msg, nonce))
+        .await?;
+
+    let height = slot_processed_subscription
+        .next()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("The rollup stopped"))??;
+    Ok(height)
+}
@@ -0,0 +1,206 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use super::test_helpers::{
+    copy_mock_genesis, create_token_call, deployer_key, subscribe_slots, update_json, with_rollup,
+};
+use borsh::BorshSerialize;
+use jsonrpsee::core::client::{ClientT, Subscription};
+use jsonrpsee::rpc_params;
+use sequencer_rewards::SequencerRewardsRpcClient;
+use sov_mock_da::{MockAddress, MockDaSpec};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::transaction::Transaction;
+use sov_modules_api::{PrivateKey, Spec};
+use sov_modules_stf_blueprint::TxEffect;
+use sov_rollup_interface::rpc::{ItemOrHash, QueryMode, SlotResponse};
+use sov_sequencer::utils::SimpleClient;
+use stf_starter::genesis_config::GenesisPaths;
+
+type Address = <DefaultContext as Spec>::Address;
+
+const GAS_PRICE: u64 = 2;
+const GAS_LIMIT: u64 = 100;
+const GAS_TIP: u64 = 3;
+const TOKEN_NAME: &str = "fee_token";
+const GAS_TOKEN_NAME: &str = "sov-gas-token";
+/// The gas tokens of the sender at genesis.
+const SENDER_BALANCE: u64 = 1000000;
+
+#[tokio::test]
+async fn tx_fees_tests() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    let fee_recipient = DefaultPrivateKey::generate().to_address();
+    let sender_key = DefaultPrivateKey::generate();
+    write_genesis_with_gas_price(genesis_dir.path(), fee_recipient, sender_key.to_address())?;
+
+    with_rollup(GenesisPaths::from_dir(genesis_dir.path()), |port| {
+        send_test_fee_txs(port, fee_recipient, sender_key)
+    })
+    .await
+}
+
+/// Copies the mock genesis, with gas metering enabled, a dedicated fee recipient and a sender
+/// funded with gas tokens, who is not the sequencer.
+fn write_genesis_with_gas_price(
+    dir: &Path,
+    fee_recipient: Address,
+    sender: Address,
+) -> Result<(), anyhow::Error> {
+    copy_mock_genesis(dir)?;
+    update_json(&dir.join("tx_fees.json"), |tx_fees| {
+        tx_fees["gas_price"] = GAS_PRICE.into();
+        tx_fees["fee_recipient"] = fee_recipient.to_string().into();
+    })?;
+    update_json(&dir.join("bank.json"), |bank| {
+        let gas_token = bank["tokens"]
+            .as_array_mut()
+            .expect("The bank genesis lists its tokens")
+            .iter_mut()
+            .find(|token| token["token_name"] == GAS_TOKEN_NAME)
+            .expect("The gas token is in the bank genesis");
+        gas_token["address_and_balances"]
+            .as_array_mut()
+            .expect("The gas token lists its balances")
+            .push(serde_json::json!([sender.to_string(), SENDER_BALANCE]));
+    })
+}
+
+fn create_token_tx(key: &DefaultPrivateKey) -> Transaction<DefaultContext> {
+    let msg = create_token_call(key, TOKEN_NAME, 0);
+    let chain_id = 0;
+    let nonce = 0;
+    Transaction::<DefaultContext>::new_signed_tx(
+        key,
+        msg.try_to_vec().unwrap(),
+        chain_id,
+        GAS_TIP,
+        GAS_LIMIT,
+        nonce,
+    )
+}
+
+async fn balance_of(
+    client: &SimpleClient,
+    user_address: Address,
+    token_address: Address,
+) -> Result<u64, anyhow::Error> {
+    let response = sov_bank::BankRpcClient::<DefaultContext>::balance_of(
+        client.http(),
+        None,
+        user_address,
+        token_address,
+    )
+    .await?;
+    Ok(response.amount.unwrap_or_default())
+}
+
+/// The rewards paid so far to the sequencer of the mock genesis.
+async fn sequencer_rewards(client: &SimpleClient) -> Result<u64, anyhow::Error> {
+    let rewards = SequencerRewardsRpcClient::<DefaultContext, MockDaSpec>::rewards_of(
+        client.http(),
+        MockAddress::from([0; 32]),
+    )
+    .await?;
+    Ok(rewards.amount)
+}
+
+/// Sends `tx` and waits for the slot that includes it, returning the effects of the
+/// transactions of that slot.
+async fn send_in_slot(
+    client: &SimpleClient,
+    slots: &mut Subscription<u64>,
+    tx: Transaction<DefaultContext>,
+) -> Result<Vec<TxEffect>, anyhow::Error> {
+    client.send_transaction(tx).await?;
+    slots
+        .next()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("The rollup stopped"))??;
+
+    let head: Option<SlotResponse<serde_json::Value, TxEffect>> = client
+        .http()
+        .request("ledger_getHead", rpc_params![QueryMode::Full])
+        .await?;
+    let head = head.ok_or_else(|| anyhow::anyhow!("The ledger is empty"))?;
+    let mut effects = Vec::new();
+    for batch in head.batches.unwrap_or_default() {
+        let ItemOrHash::Full(batch) = batch else {
+            continue;
+        };
+        for tx in batch.txs.unwrap_or_default() {
+            if let ItemOrHash::Full(tx) = tx {
+                effects.push(tx.custom_receipt);
+            }
+        }
+    }
+    Ok(effects)
+}
+
+async fn send_test_fee_txs(
+    rpc_address: SocketAddr,
+    fee_recipient: Address,
+    sender_key: DefaultPrivateKey,
+) -> Result<(), anyhow::Error> {
+    let sequencer: Address = deployer_key()?.to_address();
+    let sender: Address = sender_key.to_address();
+    let unfunded_key = DefaultPrivateKey::generate();
+    let unfunded_address: Address = unfunded_key.to_address();
+
+    let gas_token_address =
+        sov_bank::get_genesis_token_address::<DefaultContext>(GAS_TOKEN_NAME, 0);
+    let sender_token_address =
+        sov_bank::get_token_address::<DefaultContext>(TOKEN_NAME, sender.as_ref(), 0);
+    let unfunded_token_address =
+        sov_bank::get_token_address::<DefaultContext>(TOKEN_NAME, unfunded_address.as_ref(), 0);
+
+    let port = rpc_address.port();
+    let client = SimpleClient::new("localhost", port).await?;
+
+    let gas_price =
+        tx_fees::TxFeesRpcClient::<DefaultContext>::current_gas_price(client.http()).await?;
+    assert_eq!(gas_price.gas_price, GAS_PRICE);
+    assert_eq!(gas_price.token_address, Some(gas_token_address));
+
+    let mut slots = subscribe_slots(&client).await?;
+
+    // The sender of this transaction cannot cover its gas limit, so the transaction is reverted
+    let effects = send_in_slot(&client, &mut slots, create_token_tx(&unfunded_key)).await?;
+    assert!(matches!(effects[..], [TxEffect::Reverted]));
+    assert_eq!(
+        balance_of(&client, unfunded_address, unfunded_token_address).await?,
+        0
+    );
+
+    let sender_before = balance_of(&client, sender, gas_token_address).await?;
+    assert_eq!(sender_before, SENDER_BALANCE);
+    let sequencer_before = balance_of(&client, sequencer, gas_token_address).await?;
+    let rewards_before = sequencer_rewards(&client).await?;
+
+    let effects = send_in_slot(&client, &mut slots, create_token_tx(&sender_key)).await?;
+    assert!(matches!(effects[..], [TxEffect::Successful]));
+    assert_eq!(
+        balance_of(&client, sender, sender_token_address).await?,
+        1000
+    );
+
+    // The consumed gas goes to the fee recipient, and never exceeds the gas limit
+    let consumed = balance_of(&client, fee_recipient, gas_token_address).await?;
+    assert!(consumed > 0);
+    assert!(consumed <= GAS_LIMIT * GAS_PRICE);
+
+    // The sender paid for the consumed gas and the tip, the unused gas was refunded
+    assert_eq!(
+        balance_of(&client, sender, gas_token_address).await?,
+        sender_before - consumed - GAS_TIP
+    );
+
+    // The sequencer received the tip, on top of the rewards of its batch
+    let rewards = sequencer_rewards(&client).await? - rewards_before;
+    assert_eq!(
+        balance_of(&client, sequencer, gas_token_address).await?,
+        sequencer_before + GAS_TIP + rewards
+    );
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use super::test_helpers::{copy_mock_genesis, deployer_key, update_json};
+use sov_mock_da::{MockAddress, MockDaSpec};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::{PrivateKey, Spec};
+use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
+use sov_rollup_starter::genesis::{
+    genesis_hash, parse_genesis_value, read_genesis_sections, write_genesis_sections,
+    GenesisBuilder,
+};
+use stf_starter::genesis_config::{GenesisPaths, GAS_TOKEN_ADDRESS};
+use stf_starter::Runtime;
+
+type TestRuntime = Runtime<DefaultContext, MockDaSpec>;
+
+#[test]
+fn mock_genesis_is_valid() {
+    let genesis_paths = GenesisPaths::from_dir("../../test-data/genesis/mock/");
+    TestRuntime::genesis_config(&genesis_paths).unwrap();
+}
+
+/// Merges the per-module files of the mock genesis into the sections of a single genesis file.
+fn mock_genesis_file() -> Result<serde_json::Value, anyhow::Error> {
+    let mock_genesis = Path::new("../../test-data/genesis/mock/");
+    let mut genesis = serde_json::Map::new();
+    for section in [
+        "accounts",
+        "bank",
+        "sequencer_registry",
+        "sequencer_rewards",
+        "tx_fees",
+        "chain_history",
+        "chain_state",
+    ] {
+        let file = mock_genesis.join(format!("{section}.json"));
+        genesis.insert(
+            section.to_owned(),
+            serde_json::from_str(&std::fs::read_to_string(file)?)?,
+        );
+    }
+    Ok(genesis.into())
+}
+
+#[test]
+fn single_json_genesis_file_is_valid() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    let genesis_path = genesis_dir.path().join("genesis.json");
+    std::fs::write(&genesis_path, mock_genesis_file()?.to_string())?;
+
+    let genesis_paths = GenesisPaths::from_path(&genesis_path);
+    assert!(matches!(genesis_paths, GenesisPaths::File(_)));
+    TestRuntime::genesis_config(&genesis_paths)?;
+
+    let chain_state = genesis_paths
+        .read_chain_state_config()?
+        .expect("A single genesis file has a `chain_state` section");
+    assert_eq!(chain_state["initial_slot_height"], 0);
+    Ok(())
+}
+
+#[test]
+fn single_toml_genesis_file_is_valid() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    let genesis_path = genesis_dir.path().join("genesis.toml");
+    std::fs::write(&genesis_path, toml::to_string(&mock_genesis_file()?)?)?;
+
+    let genesis_paths = GenesisPaths::from_path(&genesis_path);
+    TestRuntime::genesis_config(&genesis_paths)?;
+    assert!(genesis_paths.read_chain_state_config()?.is_some());
+    Ok(())
+}
+
+#[test]
+fn genesis_dir_has_no_chain_state_section() -> Result<(), anyhow::Error> {
+    let genesis_paths = GenesisPaths::from_path("../../test-data/genesis/mock/");
+    assert!(matches!(genesis_paths, GenesisPaths::Modules(_)));
+    assert!(genesis_paths.read_chain_state_config()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn genesis_hash_does_not_depend_on_the_layout() -> Result<(), anyhow::Error> {
+    let kernel_genesis_path = Path::new("../../test-data/genesis/mock/chain_state.json");
+    let dir_sections = read_genesis_sections(
+        &GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        Some(kernel_genesis_path),
+    )?;
+
+    let genesis_dir = tempfile::tempdir()?;
+    let genesis_path = genesis_dir.path().join("genesis.toml");
+    write_genesis_sections(&dir_sections, &genesis_path)?;
+    let file_sections = read_genesis_sections(&GenesisPaths::from_file(&genesis_path), None)?;
+
+    assert_eq!(genesis_hash(&dir_sections), genesis_hash(&file_sections));
+    Ok(())
+}
+
+#[test]
+fn generated_genesis_matches_the_mock_genesis() -> Result<(), anyhow::Error> {
+    let mock_sections = read_genesis_sections(
+        &GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        Some(Path::new("../../test-data/genesis/mock/chain_state.json")),
+    )?;
+
+    let sequencer: <DefaultContext as Spec>::Address =
+        parse_genesis_value("sov1l6n2cku82yfqld30lanm2nfw43n2auc8clw7r5u5m6s7p8jrm4zqrr8r94")?;
+    let builder = GenesisBuilder::<MockDaSpec> {
+        sequencer_rollup_address: sequencer,
+        sequencer_da_address: MockAddress::from([0; 32]),
+        accounts: vec![deployer_key()?.pub_key()],
+        balances: vec![(sequencer, 100000000)],
+        token_name: "sov-gas-token".to_owned(),
+        token_salt: 0,
+        lock_token_name: "sov-demo-token".to_owned(),
+        lock_token_salt: 0,
+        lock_token_balance: 100000000,
+        coins_to_lock: 50,
+        reward_pool_address: parse_genesis_value(
+            "sov13x574fyjacee0mmqxeghy0hgxmnpts7l0qhj857dttef8lfzdpns7cdvj6",
+        )?,
+        reward_pool_balance: 1000000000,
+        gas_price: 0,
+        window_size: 1000,
+    };
+    assert_eq!(builder.token_address().to_string(), GAS_TOKEN_ADDRESS);
+
+    let generated = builder.build(&mock_sections)?;
+    assert_eq!(genesis_hash(&generated), genesis_hash(&mock_sections));
+
+    let genesis_dir = tempfile::tempdir()?;
+    write_genesis_sections(&generated, genesis_dir.path())?;
+    TestRuntime::genesis_config(&GenesisPaths::from_dir(genesis_dir.path()))?;
+    Ok(())
+}
+
+#[test]
+fn genesis_problems_are_reported_together() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    copy_mock_genesis(genesis_dir.path())?;
+
+    let unknown_minter: <DefaultContext as Spec>::Address =
+        DefaultPrivateKey::generate().to_address();
+    update_json(&genesis_dir.path().join("bank.json"), |bank| {
+        let token = bank["tokens"][0].clone();
+        let holder = token["address_and_balances"][0].clone();
+
+        let mut broken = token.clone();
+        broken["token_name"] = "broken-token".into();
+        broken["address_and_balances"] = serde_json::json!([holder.clone(), holder]);
+        broken["address_and_balances"][0][1] = u64::MAX.into();
+        broken["authorized_minters"] = serde_json::json!([unknown_minter.to_string()]);
+
+        bank["tokens"] = serde_json::json!([token.clone(), token, broken]);
+    })?;
+    update_json(
+        &genesis_dir.path().join("sequencer_registry.json"),
+        |sequencer| sequencer["coins_to_lock"]["amount"] = u64::MAX.into(),
+    )?;
+
+    let error = TestRuntime::genesis_config(&GenesisPaths::from_dir(genesis_dir.path()))
+        .err()
+        .expect("The genesis must be rejected");
+    let message = format!("{:#}", error);
+
+    assert!(message.contains("Token `sov-demo-token` with salt 0 is defined more than once"));
+    assert!(message.contains("holds token `broken-token` more than once"));
+    assert!(message.contains("The total supply of token `broken-token` overflows"));
+    assert!(message.contains(&format!(
+        "Minter {} of token `broken-token`",
+        unknown_minter
+    )));
+    assert!(message.contains("needs 18446744073709551615 of token `sov-demo-token`"));
+    Ok(())
+}
+
+#[test]
+fn empty_bank_genesis_is_rejected() -> Result<(), anyhow::Error> {
+    let genesis_dir = tempfile::tempdir()?;
+    copy_mock_genesis(genesis_dir.path())?;
+    update_json(&genesis_dir.path().join("bank.json"), |bank| {
+        bank["tokens"] = serde_json::json!([]);
+    })?;
+
+    let error = TestRuntime::genesis_config(&GenesisPaths::from_dir(genesis_dir.path()))
+        .err()
+        .expect("The genesis must be rejected");
+    let message = format!("{:#}", error);
+
+    assert!(message.contains("No token defined in `bank_config`"));
+    assert!(message.contains("is not a genesis token"));
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::net::SocketAddr;
+
+use super::test_helpers::{create_token_call, signed_tx, subscribe_slots, with_rollup};
+use indexer::IndexerRpcClient;
+use sov_bank::Coins;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::{PrivateKey, Spec};
+use sov_sequencer::utils::SimpleClient;
+use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::RuntimeCall;
+
+const TOKEN_SALT: u64 = 0;
+const TOKEN_NAME: &str = "indexed_token";
+const TRANSFER_AMOUNT: u64 = 100;
+
+#[tokio::test]
+async fn indexer_tests() -> Result<(), anyhow::Error> {
+    with_rollup(
+        GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        check_indexes,
+    )
+    .await
+}
+
+async fn check_indexes(rpc_address: SocketAddr) -> Result<(), anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    let key = DefaultPrivateKey::generate();
+    let sender: <DefaultContext as Spec>::Address = key.to_address();
+    let recipient: <DefaultContext as Spec>::Address = DefaultPrivateKey::generate().to_address();
+    let unpaid: <DefaultContext as Spec>::Address = DefaultPrivateKey::generate().to_address();
+    let token_address =
+        sov_bank::get_token_address::<DefaultContext>(TOKEN_NAME, sender.as_ref(), TOKEN_SALT);
+
+    let mut slot_processed_subscription = subscribe_slots(&client).await?;
+
+    let create_token = create_token_call(&key, TOKEN_NAME, TOKEN_SALT);
+    client
+        .send_transaction(signed_tx(&key, create_token, 0))
+        .await?;
+    let _ = slot_processed_subscription.next().await;
+
+    let transfer = RuntimeCall::bank(sov_bank::CallMessage::Transfer {
+        to: recipient,
+        coins: Coins {
+            amount: TRANSFER_AMOUNT,
+            token_address,
+        },
+    });
+    client
+        .send_transaction(signed_tx(&key, transfer, 1))
+        .await?;
+    let _ = slot_processed_subscription.next().await;
+
+    // Reverted, the sender holds less than the amount
+    let reverted_transfer = RuntimeCall::bank(sov_bank::CallMessage::Transfer {
+        to: unpaid,
+        coins: Coins {
+            amount: 1_000_000,
+            token_address,
+        },
+    });
+    client
+        .send_transaction(signed_tx(&key, reverted_transfer, 2))
+        .await?;
+    let _ = slot_processed_subscription.next().await;
+
+    // Not a transfer event, it changes no balance
+    let self_transfer = RuntimeCall::bank(sov_bank::CallMessage::Transfer {
+        to: sender,
+        coins: Coins {
+            amount: TRANSFER_AMOUNT,
+            token_address,
+        },
+    });
+    client
+        .send_transaction(signed_tx(&key, self_transfer, 3))
+        .await?;
+    let _ = slot_processed_subscription.next().await;
+
+    let txs =
+        IndexerRpcClient::<DefaultContext>::transactions_by_sender(client.http(), sender, 0, 10)
+            .await?;
+    assert_eq!(txs.total, 4);
+    assert_eq!(
+        txs.items.iter().map(|tx| tx.nonce).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3]
+    );
+
+    let holders =
+        IndexerRpcClient::<DefaultContext>::token_holders_of(client.http(), token_address, 0, 10)
+            .await?;
+    assert_eq!(holders.items, vec![sender, recipient]);
+
+    // Pagination
+    let second_holder =
+        IndexerRpcClient::<DefaultContext>::token_holders_of(client.http(), token_address, 1, 1)
+            .await?;
+    assert_eq!(second_holder.total, 2);
+    assert_eq!(second_holder.items, vec![recipient]);
+
+    let accounts = IndexerRpcClient::<DefaultContext>::accounts(client.http(), 0, 10).await?;
+    assert_eq!(accounts.items, vec![key.pub_key()]);
+
+    let tokens = IndexerRpcClient::<DefaultContext>::tokens(client.http(), 0, 10).await?;
+    assert_eq!(tokens.items, vec![token_address]);
+
+    let transfers =
+        IndexerRpcClient::<DefaultContext>::transfers_of(client.http(), recipient, 0, 10).await?;
+    assert_eq!(transfers.total, 1);
+    assert_eq!(transfers.items[0].from, sender);
+    assert_eq!(transfers.items[0].coins.amount, TRANSFER_AMOUNT);
+
+    let reverted =
+        IndexerRpcClient::<DefaultContext>::transfers_of(client.http(), unpaid, 0, 10).await?;
+    assert_eq!(reverted.total, 0);
+    let sender_transfers =
+        IndexerRpcClient::<DefaultContext>::transfers_of(client.http(), sender, 0, 10).await?;
+    assert_eq!(sender_transfers.total, 1);
+    Ok(())
+}
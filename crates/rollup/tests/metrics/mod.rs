@@ -0,0 +1,68 @@
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::rpc_params;
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::RpcModule;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use sov_rollup_starter::metrics::start_rpc_server;
+use sov_sequencer::utils::SimpleClient;
+use sov_stf_runner::RpcConfig;
+use tokio::sync::oneshot;
+
+#[tokio::test]
+async fn rpc_metrics_tests() -> Result<(), anyhow::Error> {
+    let recorder = PrometheusBuilder::new().install_recorder()?;
+
+    let mut rpc_methods = RpcModule::new(());
+    rpc_methods.register_method("test_add", |params, _| {
+        let (a, b): (u64, u64) = params.parse()?;
+        RpcResult::Ok(a + b)
+    })?;
+    rpc_methods.register_method("test_fail", |_, _| {
+        RpcResult::<()>::Err(jsonrpsee::core::Error::Call(ErrorObjectOwned::owned(
+            -32001,
+            "test failure",
+            None::<()>,
+        )))
+    })?;
+
+    let (port_tx, port_rx) = oneshot::channel();
+    let rpc_config = RpcConfig {
+        bind_host: "127.0.0.1".into(),
+        bind_port: 0,
+    };
+    let _server = start_rpc_server(rpc_methods, &rpc_config, Some(port_tx)).await?;
+    let client = SimpleClient::new("localhost", port_rx.await?.port()).await?;
+
+    let sum: u64 = client
+        .http()
+        .request("test_add", rpc_params![1u64, 2u64])
+        .await?;
+    assert_eq!(sum, 3);
+    let sum: u64 = client
+        .http()
+        .request("test_add", rpc_params![3u64, 4u64])
+        .await?;
+    assert_eq!(sum, 7);
+    let error = client
+        .http()
+        .request::<(), _>("test_fail", rpc_params![])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("test failure"));
+    assert!(client
+        .http()
+        .request::<(), _>("test_missing", rpc_params![])
+        .await
+        .is_err());
+
+    let metrics = recorder.render();
+    assert!(metrics.contains(r#"rpc_requests_total{method="test_add"} 2"#));
+    assert!(metrics.contains(r#"rpc_request_duration_seconds_count{method="test_add"} 2"#));
+    assert!(!metrics.contains(r#"rpc_request_errors_total{method="test_add"}"#));
+    assert!(metrics.contains(r#"rpc_request_errors_total{method="test_fail"} 1"#));
+    // The methods that are not served are not added as labels
+    assert!(metrics.contains(r#"rpc_request_errors_total{method="unknown"}"#));
+    assert!(!metrics.contains("test_missing"));
+    Ok(())
+}
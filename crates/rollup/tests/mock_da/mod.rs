@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use super::test_helpers::{mock_da_settings, send_create_token, Node};
+use chain_history::ChainHistoryRpcClient;
+use sov_mock_da::MockAddress;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_rollup_interface::da::{BlobReaderTrait, BlockHeaderTrait};
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_starter::mock_rollup::MockDaSettings;
+use sov_rollup_starter::persistent_mock_da::{BlockProduction, PersistentMockDaService, Reorgs};
+use sov_sequencer::utils::SimpleClient;
+
+async fn head_height(service: &PersistentMockDaService) -> Result<u64, anyhow::Error> {
+    Ok(service.get_head_block_header().await?.height())
+}
+
+/// Waits until `service` has replayed the submissions of the other nodes.
+async fn wait_for_height(
+    service: &PersistentMockDaService,
+    height: u64,
+) -> Result<(), anyhow::Error> {
+    for _ in 0..50 {
+        if head_height(service).await? >= height {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("The mock DA did not reach height {}", height)
+}
+
+#[tokio::test]
+async fn persistent_mock_da_tests() -> Result<(), anyhow::Error> {
+    let dir = tempfile::tempdir()?;
+    let sender = MockAddress::from([0; 32]);
+    let other_sender = MockAddress::from([1; 32]);
+
+    let sequencer =
+        PersistentMockDaService::open(sender, dir.path(), BlockProduction::default()).await?;
+    let full_node =
+        PersistentMockDaService::open(other_sender, dir.path(), BlockProduction::default()).await?;
+    let initial_height = head_height(&sequencer).await?;
+
+    sequencer.send_transaction(&[1; 16]).await?;
+    full_node.send_transaction(&[2; 16]).await?;
+    let height = head_height(&full_node).await?;
+    assert_eq!(height, initial_height + 2);
+
+    // Both nodes see the blocks submitted by the other, from the address that submitted them
+    wait_for_height(&sequencer, height).await?;
+    let mut blocks = Vec::new();
+    for (block_height, expected_sender) in (initial_height + 1..=height).zip([sender, other_sender])
+    {
+        let block = sequencer.get_block_at(block_height).await?;
+        let blobs = sequencer.extract_relevant_blobs(&block);
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].sender(), expected_sender);
+        assert_eq!(
+            block.header,
+            full_node.get_block_at(block_height).await?.header
+        );
+        blocks.push(block);
+    }
+
+    // A restarted node resumes from the persisted chain, with the same blocks
+    drop(sequencer);
+    drop(full_node);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let restarted =
+        PersistentMockDaService::open(other_sender, dir.path(), BlockProduction::default()).await?;
+    assert_eq!(head_height(&restarted).await?, height);
+    for block in blocks {
+        let replayed = restarted.get_block_at(block.header.height()).await?;
+        assert_eq!(replayed.header, block.header);
+        assert_eq!(replayed.blobs[0].sender(), block.blobs[0].sender());
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_da_block_production_tests() -> Result<(), anyhow::Error> {
+    let service = PersistentMockDaService::in_memory(
+        MockAddress::from([0; 32]),
+        BlockProduction {
+            block_time: Some(Duration::from_millis(100)),
+            produce_empty_blocks: true,
+            max_blob_size: Some(64),
+            blocks_to_finality: 2,
+            reorgs: None,
+        },
+    )?;
+
+    // Oversized blobs are rejected
+    assert!(service.send_transaction(&[1; 65]).await.is_err());
+    service.send_transaction(&[1; 64]).await?;
+    let blob_height = head_height(&service).await?;
+
+    // Empty blocks are produced while nothing is submitted, without any blob
+    wait_for_height(&service, blob_height + 3).await?;
+    let block = service.get_block_at(blob_height).await?;
+    assert_eq!(service.extract_relevant_blobs(&block).len(), 1);
+    let block = service.get_block_at(blob_height + 1).await?;
+    assert!(block.blobs.is_empty());
+
+    // Blocks are final once enough blocks were produced on top of them
+    // (blocks keep being produced while the heights are read)
+    let head_before = head_height(&service).await?;
+    let finalized = service.get_last_finalized_block_header().await?.height();
+    let head_after = head_height(&service).await?;
+    assert!(finalized + 2 >= head_before && finalized + 2 <= head_after);
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_da_reorg_tests() -> Result<(), anyhow::Error> {
+    let production = BlockProduction {
+        blocks_to_finality: 2,
+        reorgs: Some(Reorgs {
+            depth: 2,
+            interval: 4,
+        }),
+        ..Default::default()
+    };
+    let service = PersistentMockDaService::in_memory(MockAddress::from([0; 32]), production)?;
+
+    let mut heads = Vec::new();
+    for blob in 1..=3 {
+        service.send_transaction(&[blob; 16]).await?;
+        heads.push(service.get_head_block_header().await?);
+    }
+
+    // The 4th block replaces the blocks 3 and 4, which are not final
+    service.send_transaction(&[4; 16]).await?;
+    let head = service.get_head_block_header().await?;
+    assert_eq!(head.height(), 4);
+    assert_ne!(head.prev_hash(), heads[2].hash());
+    assert_eq!(service.get_last_finalized_block_header().await?.height(), 2);
+    for height in 1..=2 {
+        let block = service.get_block_at(height).await?;
+        assert_eq!(block.header, heads[height as usize - 1]);
+    }
+
+    // Only final blocks are returned, so the rollup never sees the replaced blocks
+    let not_final = tokio::time::timeout(Duration::from_millis(200), service.get_block_at(3));
+    assert!(not_final.await.is_err());
+    for blob in 5..=6 {
+        service.send_transaction(&[blob; 16]).await?;
+    }
+    let reorged = service.get_block_at(3).await?;
+    assert_ne!(reorged.header.hash(), heads[2].hash());
+    assert_eq!(reorged.header.prev_hash(), heads[1].hash());
+    assert_eq!(reorged.blobs.len(), 1);
+    assert_eq!(service.get_block_at(4).await?.header, head);
+
+    // Final blocks never change
+    let too_deep = BlockProduction {
+        blocks_to_finality: 1,
+        reorgs: Some(Reorgs {
+            depth: 2,
+            interval: 4,
+        }),
+        ..Default::default()
+    };
+    assert!(PersistentMockDaService::in_memory(MockAddress::from([0; 32]), too_deep).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_da_reorg_consistency_tests() -> Result<(), anyhow::Error> {
+    let da_dir = tempfile::tempdir()?;
+    let sequencer_dir = tempfile::tempdir()?;
+    let full_node_dir = tempfile::tempdir()?;
+    let da = MockDaSettings {
+        block_time_ms: Some(100),
+        blocks_to_finality: 2,
+        reorg_depth: Some(2),
+        reorg_interval: 3,
+        ..mock_da_settings(Some(da_dir.path()))
+    };
+    let sequencer = Node::start_with_da(
+        sequencer_dir.path(),
+        MockDaSettings {
+            produce_empty_blocks: true,
+            ..da.clone()
+        },
+    )
+    .await?;
+    let full_node = Node::start_with_da(full_node_dir.path(), da).await?;
+
+    // Every 3 blocks, the last 2 blocks are reorged while the transactions are processed
+    let key = DefaultPrivateKey::generate();
+    for nonce in 0..3 {
+        send_create_token(sequencer.rpc_address, &key, nonce).await?;
+    }
+    let sequencer_client = SimpleClient::new("localhost", sequencer.rpc_address.port()).await?;
+    let full_node_client = SimpleClient::new("localhost", full_node.rpc_address.port()).await?;
+    let latest =
+        ChainHistoryRpcClient::<DefaultContext>::latest_slot(sequencer_client.http()).await?;
+    let latest_da_height = latest.slot.expect("The latest slot is recorded").da_height;
+    assert!(latest_da_height >= 3);
+
+    let mut synced = false;
+    for _ in 0..50 {
+        let full_node_latest =
+            ChainHistoryRpcClient::<DefaultContext>::latest_slot(full_node_client.http()).await?;
+        if full_node_latest.height >= latest.height {
+            synced = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(
+        synced,
+        "The full node did not process the slots of the sequencer"
+    );
+
+    // Both nodes processed the same final blocks, so they have the same state roots
+    for height in 1..=latest.height {
+        let processed =
+            ChainHistoryRpcClient::<DefaultContext>::slot(sequencer_client.http(), height).await?;
+        let replayed =
+            ChainHistoryRpcClient::<DefaultContext>::slot(full_node_client.http(), height).await?;
+        assert!(processed.slot.is_some());
+        assert_eq!(processed.slot, replayed.slot);
+    }
+
+    sequencer.stop().await?;
+    full_node.stop().await?;
+    Ok(())
+}
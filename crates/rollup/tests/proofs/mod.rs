@@ -0,0 +1,71 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+use borsh::BorshDeserialize;
+use sov_mock_da::MockAddress;
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_starter::persistent_mock_da::{BlockProduction, PersistentMockDaService};
+use sov_rollup_starter::proofs::{AggregatedProof, ProofPublisher, ProvenSlotRecord, SlotProof};
+use sov_rollup_starter::pruning::last_proven_slot;
+use tokio::sync::mpsc;
+
+async fn published_proofs(
+    service: &PersistentMockDaService,
+    height: u64,
+) -> Result<Vec<AggregatedProof>, anyhow::Error> {
+    let mut aggregated_proofs = Vec::new();
+    for blob in service.get_aggregated_proofs_at(height).await? {
+        aggregated_proofs.push(AggregatedProof::try_from_slice(&blob)?);
+    }
+    Ok(aggregated_proofs)
+}
+
+#[tokio::test]
+async fn proof_publisher_tests() -> Result<(), anyhow::Error> {
+    let service =
+        PersistentMockDaService::in_memory(MockAddress::from([0; 32]), BlockProduction::default())?;
+    let storage_dir = tempfile::tempdir()?;
+    // The prover started after the slot 10 of the ledger
+    let proven_slots = ProvenSlotRecord {
+        storage_path: storage_dir.path().to_path_buf(),
+        first_slot: Arc::new(AtomicU64::new(11)),
+    };
+    let (proofs, receiver) = mpsc::unbounded_channel();
+    let publisher =
+        tokio::spawn(ProofPublisher::new(service.clone(), 2, receiver, Some(proven_slots)).run());
+
+    // The proofs are published in slot order, without the slots that were not proven
+    for (slot, proof) in [
+        (1, Some(vec![1])),
+        (0, Some(vec![0])),
+        (2, None),
+        (3, Some(vec![3])),
+    ] {
+        proofs.send(SlotProof { slot, proof })?;
+    }
+    // The slot 4 is never reported, for instance because its proof panicked
+    proofs.send(SlotProof {
+        slot: 5,
+        proof: Some(vec![5]),
+    })?;
+
+    // Once the prover is dropped, the remaining proofs are published, even if there are fewer
+    // than `aggregated_proof_block_jump` of them
+    drop(proofs);
+    publisher.await?;
+    assert_eq!(
+        published_proofs(&service, 1).await?,
+        vec![AggregatedProof {
+            proofs: vec![vec![0], vec![1]],
+        }]
+    );
+    assert_eq!(
+        published_proofs(&service, 2).await?,
+        vec![AggregatedProof {
+            proofs: vec![vec![3], vec![5]],
+        }]
+    );
+    // The last published proof is the one of the slot 5 of the prover
+    assert_eq!(last_proven_slot(storage_dir.path())?, Some(16));
+    Ok(())
+}
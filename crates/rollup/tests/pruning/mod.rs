@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use super::test_helpers::{send_create_token, Node};
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_rollup_starter::pruning::{
+    check_state_schema, db_stats, last_proven_slot, oldest_readable_version_of, prune_state,
+    record_proven_slot, PruningConfig, PruningPolicy,
+};
+
+#[tokio::test]
+async fn pruning_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let key = DefaultPrivateKey::generate();
+
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let mut height = 0;
+    for nonce in 0..3 {
+        height = send_create_token(node.rpc_address, &key, nonce).await?;
+    }
+    node.stop().await?;
+
+    let stats = db_stats(storage_dir.path())?;
+    let state = stats
+        .iter()
+        .find(|database| database.name == "state")
+        .expect("The state database is listed");
+    assert!(state.size > 0);
+    assert!(stats.iter().any(|database| database.name == "ledger"));
+
+    // The prover window keeps the versions from the state after the last proven slot, which the
+    // node does not prove
+    let prover_window = PruningPolicy::ProverWindow;
+    assert_eq!(last_proven_slot(storage_dir.path())?, None);
+    assert_eq!(
+        prover_window.versions_to_keep(storage_dir.path())?,
+        Some(height + 1)
+    );
+    record_proven_slot(storage_dir.path(), height - 1)?;
+    assert_eq!(last_proven_slot(storage_dir.path())?, Some(height - 1));
+    assert_eq!(prover_window.versions_to_keep(storage_dir.path())?, Some(2));
+
+    check_state_schema(storage_dir.path())?;
+    assert_eq!(oldest_readable_version_of(storage_dir.path())?, 1);
+    // The account nonce and the chain history are written at every slot
+    let pruned = prune_state(storage_dir.path(), 1)?.expect("The state is pruned");
+    assert_eq!(pruned.oldest_readable_version, height + 1);
+    assert!(pruned.deleted_values > 0);
+    assert_eq!(
+        oldest_readable_version_of(storage_dir.path())?,
+        pruned.oldest_readable_version
+    );
+    assert_eq!(prune_state(storage_dir.path(), 1)?, None);
+
+    // The running node pauses to prune `pruning_interval` slots after the last one
+    let config_path = storage_dir.path().join("rollup_config.toml");
+    std::fs::write(
+        &config_path,
+        "[storage]\npath = \"unused\"\npruning_interval = 10\n\n\
+         [storage.pruning]\nmode = \"keep_last\"\nversions = 1\n",
+    )?;
+    let pruning = PruningConfig::from_toml_path(&config_path)?;
+    assert_eq!(pruning.policy, PruningPolicy::KeepLast { versions: 1 });
+    assert_eq!(pruning.interval, 10);
+    assert_eq!(
+        pruning.pause_height(storage_dir.path(), 1)?,
+        Some(1 + height + 10 - 1)
+    );
+
+    // The pruned node resumes from the latest state
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let next_height = send_create_token(node.rpc_address, &key, 3).await?;
+    assert_eq!(next_height, height + 1);
+    node.stop().await?;
+
+    // Nothing is deleted from a state database with another schema
+    let state_path = storage_dir.path().join("state");
+    let column_families = rocksdb::DB::list_cf(&rocksdb::Options::default(), &state_path)?;
+    let mut db = rocksdb::DB::open_cf(&rocksdb::Options::default(), &state_path, column_families)?;
+    db.create_cf("jmt_values_v2", &rocksdb::Options::default())?;
+    drop(db);
+    let error = prune_state(storage_dir.path(), 1).unwrap_err();
+    assert!(error.to_string().contains("jmt_values_v2"));
+    assert!(check_state_schema(storage_dir.path()).is_err());
+    assert_eq!(oldest_readable_version_of(storage_dir.path())?, height + 1);
+    Ok(())
+}
+
+#[test]
+fn pruning_policy_tests() -> Result<(), anyhow::Error> {
+    // These policies do not depend on the storage
+    let storage_path = Path::new("unused");
+    let policy: PruningPolicy = toml::from_str("mode = \"keep_last\"\nversions = 10")?;
+    assert_eq!(policy, PruningPolicy::KeepLast { versions: 10 });
+    assert_eq!(policy.versions_to_keep(storage_path)?, Some(10));
+    assert_eq!(
+        PruningPolicy::default().versions_to_keep(storage_path)?,
+        None
+    );
+
+    let policy: PruningPolicy = toml::from_str("mode = \"prover_window\"")?;
+    assert_eq!(policy, PruningPolicy::ProverWindow);
+    Ok(())
+}
+
+#[test]
+fn pruning_config_tests() -> Result<(), anyhow::Error> {
+    let dir = tempfile::tempdir()?;
+    let config_path = dir.path().join("rollup_config.toml");
+    std::fs::write(&config_path, "[storage]\npath = \"unused\"\n")?;
+    let pruning = PruningConfig::from_toml_path(&config_path)?;
+    assert_eq!(pruning.policy, PruningPolicy::Archive);
+    assert_eq!(pruning.interval, 1000);
+    // An archive node never pauses
+    assert_eq!(pruning.pause_height(dir.path(), 1)?, None);
+    Ok(())
+}
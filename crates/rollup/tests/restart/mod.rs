@@ -0,0 +1,73 @@
+use super::test_helpers::{mock_da_settings, run_rollup_until_shutdown, send_create_token, Node};
+use sov_accounts::AccountsRpcClient;
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisPaths;
+use sov_rollup_starter::consistency::{check_storage, StorageCheck};
+use sov_sequencer::utils::SimpleClient;
+use sov_stf_runner::RollupProverConfig;
+use stf_starter::genesis_config::GenesisPaths;
+use tokio::sync::oneshot;
+
+#[tokio::test]
+async fn restart_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let key = DefaultPrivateKey::generate();
+
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let height = send_create_token(node.rpc_address, &key, 0).await?;
+    node.stop().await?;
+
+    // The restarted node resumes from the next height, with the state of the first run
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let next_height = send_create_token(node.rpc_address, &key, 1).await?;
+    assert_eq!(next_height, height + 1);
+
+    let client = SimpleClient::new("localhost", node.rpc_address.port()).await?;
+    let account =
+        AccountsRpcClient::<DefaultContext>::get_account(client.http(), key.pub_key()).await?;
+    assert!(matches!(
+        account,
+        sov_accounts::Response::AccountExists { nonce: 2, .. }
+    ));
+
+    node.stop().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn shutdown_closes_the_databases() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let (port_tx, port_rx) = oneshot::channel();
+    let (shutdown_signal, shutdown_rx) = oneshot::channel();
+
+    let run = run_rollup_until_shutdown(
+        port_tx,
+        GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        BasicKernelGenesisPaths {
+            chain_state: "../../test-data/genesis/mock/chain_state.json".into(),
+        },
+        RollupProverConfig::Skip,
+        storage_dir.path(),
+        mock_da_settings(Some(da_dir.path())),
+        shutdown_rx,
+    );
+    let send = async {
+        let height = send_create_token(port_rx.await?, &DefaultPrivateKey::generate(), 0).await;
+        let _ = shutdown_signal.send(());
+        height
+    };
+    let (run, height) = tokio::join!(run, send);
+    run?;
+
+    // The databases are closed in the runtime of the node, so they can be opened again
+    assert_eq!(
+        check_storage::<MockDaSpec>(storage_dir.path(), 1)?,
+        StorageCheck::Consistent { height: height? }
+    );
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use sov_mock_da::{MockAddress, MockDaSpec};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::{Genesis, Spec, WorkingSet};
+use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
+use sov_rollup_starter::export::open_storage;
+use sov_rollup_starter::genesis::parse_genesis_value;
+use stf_starter::genesis_config::{GenesisPaths, GAS_TOKEN_ADDRESS};
+use stf_starter::Runtime;
+
+type Address = <DefaultContext as Spec>::Address;
+
+const REWARD: u64 = 40;
+/// The sequencer of the mock genesis, registered with this rollup address.
+const SEQUENCER_ADDRESS: &str = "sov1l6n2cku82yfqld30lanm2nfw43n2auc8clw7r5u5m6s7p8jrm4zqrr8r94";
+/// The reward pool of the mock `sequencer_rewards.json`.
+const REWARD_POOL_ADDRESS: &str = "sov13x574fyjacee0mmqxeghy0hgxmnpts7l0qhj857dttef8lfzdpns7cdvj6";
+
+#[test]
+fn rewards_are_paid_from_the_pool_to_the_registered_address() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let storage = open_storage::<MockDaSpec>(storage_dir.path())?;
+    let mut working_set = WorkingSet::new(storage);
+
+    let runtime = Runtime::<DefaultContext, MockDaSpec>::default();
+    let config = Runtime::<DefaultContext, MockDaSpec>::genesis_config(&GenesisPaths::from_dir(
+        "../../test-data/genesis/mock/",
+    ))?;
+    runtime.genesis(&config, &mut working_set)?;
+
+    let sequencer_da_address = MockAddress::from([0; 32]);
+    let sequencer: Address = parse_genesis_value(SEQUENCER_ADDRESS)?;
+    let reward_pool: Address = parse_genesis_value(REWARD_POOL_ADDRESS)?;
+    let gas_token: Address = parse_genesis_value(GAS_TOKEN_ADDRESS)?;
+    let balance_of = |address: Address, working_set: &mut WorkingSet<DefaultContext>| {
+        runtime
+            .bank
+            .get_balance_of(address, gas_token, working_set)
+            .unwrap_or_default()
+    };
+
+    let sequencer_before = balance_of(sequencer, &mut working_set);
+    let pool_before = balance_of(reward_pool, &mut working_set);
+
+    let rewards = &runtime.sequencer_rewards;
+    assert_eq!(
+        rewards.get_payout_address(&sequencer_da_address, &mut working_set),
+        Some(sequencer)
+    );
+    rewards.begin_blob_hook(&sequencer_da_address, &mut working_set);
+    rewards.reward_sequencer(REWARD, &mut working_set);
+
+    assert_eq!(
+        balance_of(sequencer, &mut working_set),
+        sequencer_before + REWARD
+    );
+    assert_eq!(
+        balance_of(reward_pool, &mut working_set),
+        pool_before - REWARD
+    );
+    assert_eq!(
+        rewards.get_cumulative_rewards(&sequencer_da_address, &mut working_set),
+        REWARD
+    );
+
+    // A sender that is not registered is not paid
+    let unregistered = MockAddress::from([1; 32]);
+    rewards.begin_blob_hook(&unregistered, &mut working_set);
+    rewards.reward_sequencer(REWARD, &mut working_set);
+    assert_eq!(
+        balance_of(reward_pool, &mut working_set),
+        pool_before - REWARD
+    );
+    assert_eq!(
+        rewards.get_cumulative_rewards(&unregistered, &mut working_set),
+        0
+    );
+    assert_eq!(
+        rewards.get_unpaid_rewards(&unregistered, &mut working_set),
+        REWARD
+    );
+
+    // A registration is paid to its sender once the registry locked its coins
+    let locked = runtime
+        .sequencer_registry
+        .get_coins_to_lock(&mut working_set)
+        .expect("The coins to lock are set at genesis");
+    let register = |da_address: MockAddress, exit: bool, working_set: &mut WorkingSet<_>| {
+        rewards.begin_registration(da_address, sequencer, exit, locked.clone(), working_set)
+    };
+    register(unregistered, false, &mut working_set);
+    rewards.end_registration(&mut working_set);
+    assert_eq!(
+        rewards.get_payout_address(&unregistered, &mut working_set),
+        None
+    );
+    register(unregistered, false, &mut working_set);
+    runtime
+        .bank
+        .transfer_from(&sequencer, &reward_pool, locked.clone(), &mut working_set)?;
+    rewards.end_registration(&mut working_set);
+    assert_eq!(
+        rewards.get_payout_address(&unregistered, &mut working_set),
+        Some(sequencer)
+    );
+
+    // An exit stops the payouts once the registry returned the coins
+    register(unregistered, true, &mut working_set);
+    runtime
+        .bank
+        .transfer_from(&reward_pool, &sequencer, locked.clone(), &mut working_set)?;
+    rewards.end_registration(&mut working_set);
+    assert_eq!(
+        rewards.get_payout_address(&unregistered, &mut working_set),
+        None
+    );
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::net::SocketAddr;
+
+use super::test_helpers::{subscribe_slots, with_rollup};
+use jsonrpsee::core::client::ClientT;
+use sequencer_rewards::{SequencerRewardsRpcClient, SlashReason};
+use sov_mock_da::{MockAddress, MockDaSpec};
+use sov_modules_api::default_context::DefaultContext;
+use sov_sequencer::utils::SimpleClient;
+use sov_sequencer_registry::SequencerRegistryRpcClient;
+use stf_starter::genesis_config::GenesisPaths;
+
+/// The `coins_to_lock` amount of the mock `sequencer_registry.json`.
+const LOCKED_AMOUNT: u64 = 50;
+
+#[tokio::test]
+async fn slashing_tests() -> Result<(), anyhow::Error> {
+    with_rollup(
+        GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        send_malformed_batch,
+    )
+    .await
+}
+
+async fn send_malformed_batch(rpc_address: SocketAddr) -> Result<(), anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    let sequencer = MockAddress::from([0; 32]);
+
+    let mut slot_processed_subscription = subscribe_slots(&client).await?;
+
+    let no_slashes =
+        SequencerRewardsRpcClient::<DefaultContext, MockDaSpec>::slashes(client.http(), 0, 10)
+            .await?;
+    assert_eq!(no_slashes.total, 0);
+
+    // A batch whose only transaction cannot be deserialized
+    let batch = vec![vec![0xffu8; 16]];
+    let _: String = client
+        .http()
+        .request("sequencer_publishBatch", batch)
+        .await?;
+
+    // Wait until the rollup has processed the next slot
+    let _ = slot_processed_subscription.next().await;
+
+    let slashes =
+        SequencerRewardsRpcClient::<DefaultContext, MockDaSpec>::slashes(client.http(), 0, 10)
+            .await?;
+    assert_eq!(slashes.total, 1);
+    let slash = &slashes.slashes[0];
+    assert_eq!(slash.sequencer_da_address, sequencer);
+    assert_eq!(slash.reason, SlashReason::InvalidTransactionEncoding);
+    assert_eq!(
+        slash.forfeited.as_ref().map(|coins| coins.amount),
+        Some(LOCKED_AMOUNT)
+    );
+    assert!(slash.height > 0);
+
+    let registered = SequencerRegistryRpcClient::<DefaultContext, MockDaSpec>::sequencer_address(
+        client.http(),
+        sequencer,
+    )
+    .await?;
+    assert_eq!(registered.address, None);
+    Ok(())
+}
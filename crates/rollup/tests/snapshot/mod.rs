@@ -0,0 +1,81 @@
+use super::test_helpers::{send_create_token, Node};
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_rollup_starter::snapshot::{create_snapshot, read_manifest, restore_snapshot};
+
+#[tokio::test]
+async fn snapshot_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let snapshot_dir = tempfile::tempdir()?;
+    let key = DefaultPrivateKey::generate();
+
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    let height = send_create_token(node.rpc_address, &key, 0).await?;
+    node.stop().await?;
+
+    // Snapshots are written at the last height of the storage
+    let other_snapshot_dir = tempfile::tempdir()?;
+    assert!(create_snapshot::<MockDaSpec>(
+        storage_dir.path(),
+        1,
+        Some(height - 1),
+        other_snapshot_dir.path()
+    )
+    .is_err());
+    let manifest =
+        create_snapshot::<MockDaSpec>(storage_dir.path(), 1, Some(height), snapshot_dir.path())?;
+    assert_eq!(manifest.height, height);
+    assert_eq!(manifest.da_height, height);
+    assert_eq!(read_manifest(snapshot_dir.path())?, manifest);
+
+    // A snapshot is only restored to an empty storage, with the same start height
+    assert!(restore_snapshot::<MockDaSpec>(snapshot_dir.path(), storage_dir.path(), 1).is_err());
+    let new_storage_dir = tempfile::tempdir()?;
+    assert!(
+        restore_snapshot::<MockDaSpec>(snapshot_dir.path(), new_storage_dir.path(), 3).is_err()
+    );
+    assert_eq!(
+        restore_snapshot::<MockDaSpec>(snapshot_dir.path(), new_storage_dir.path(), 1)?,
+        manifest
+    );
+
+    // The new node continues syncing from the height of the snapshot
+    let node = Node::start(new_storage_dir.path(), da_dir.path()).await?;
+    let next_height = send_create_token(node.rpc_address, &key, 1).await?;
+    assert_eq!(next_height, height + 1);
+    node.stop().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn tampered_snapshot_tests() -> Result<(), anyhow::Error> {
+    let storage_dir = tempfile::tempdir()?;
+    let da_dir = tempfile::tempdir()?;
+    let snapshot_dir = tempfile::tempdir()?;
+    let key = DefaultPrivateKey::generate();
+
+    let node = Node::start(storage_dir.path(), da_dir.path()).await?;
+    send_create_token(node.rpc_address, &key, 0).await?;
+    node.stop().await?;
+
+    let mut manifest =
+        create_snapshot::<MockDaSpec>(storage_dir.path(), 1, None, snapshot_dir.path())?;
+    manifest.state_root = hex::encode([0u8; 32]);
+    std::fs::write(
+        snapshot_dir.path().join("manifest.json"),
+        serde_json::to_string(&manifest)?,
+    )?;
+
+    // A snapshot that does not match its manifest never reaches the storage
+    let new_storage_dir = tempfile::tempdir()?;
+    let new_storage = new_storage_dir.path().join("storage");
+    std::fs::create_dir(&new_storage)?;
+    assert!(restore_snapshot::<MockDaSpec>(snapshot_dir.path(), &new_storage, 1).is_err());
+    assert!(new_storage.exists());
+    assert_eq!(std::fs::read_dir(&new_storage)?.count(), 0);
+    // Nor is it left next to the storage
+    assert_eq!(std::fs::read_dir(new_storage_dir.path())?.count(), 1);
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::net::SocketAddr;
+
+use super::test_helpers::{send_create_token, with_rollup};
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::rpc_params;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::PrivateKey;
+use sov_rollup_starter::status::{SyncStatus, DEFAULT_MAX_LAG};
+use sov_sequencer::utils::SimpleClient;
+use stf_starter::genesis_config::GenesisPaths;
+
+#[tokio::test]
+async fn sync_status_tests() -> Result<(), anyhow::Error> {
+    with_rollup(
+        GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+        check_sync_status,
+    )
+    .await
+}
+
+async fn check_sync_status(rpc_address: SocketAddr) -> Result<(), anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    send_create_token(rpc_address, &DefaultPrivateKey::generate(), 0).await?;
+
+    let status: SyncStatus = client
+        .http()
+        .request("node_syncStatus", rpc_params![])
+        .await?;
+    assert!(status.rollup_height >= 1);
+    assert!(status.last_processed_da_height.is_some());
+    assert!(status.state_root.is_some());
+    assert!(status.lag <= DEFAULT_MAX_LAG);
+    assert!(status.ready);
+    assert!(!status.stalled);
+    // The test rollup does not follow the proofs posted to the DA layer
+    assert!(status.last_verified_proof.is_none());
+    // The prover runs in the `execute` mode, so its backlog is bounded by the processed slots
+    assert!(status.prover_backlog <= status.rollup_height);
+    Ok(())
+}
@@ -1,14 +1,27 @@
+use std::future::Future;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
 
-use sov_mock_da::{MockAddress, MockDaConfig};
+use borsh::BorshSerialize;
+use jsonrpsee::core::client::{Subscription, SubscriptionClientT};
+use jsonrpsee::rpc_params;
+use sov_mock_da::{MockAddress, MockDaSpec};
+use sov_modules_api::default_context::DefaultContext;
+use sov_modules_api::default_signature::private_key::DefaultPrivateKey;
+use sov_modules_api::transaction::Transaction;
+use sov_modules_api::PrivateKey;
 use sov_modules_rollup_blueprint::RollupBlueprint;
 use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisConfig;
 use sov_modules_stf_blueprint::kernels::basic::BasicKernelGenesisPaths;
-use sov_rollup_starter::mock_rollup::MockRollup;
+use sov_rollup_starter::mock_rollup::{MockDaSettings, MockRollup};
+use sov_rollup_starter::shutdown::run_until_shutdown;
+use sov_sequencer::utils::SimpleClient;
 use sov_stf_runner::ProverServiceConfig;
 use sov_stf_runner::RollupProverConfig;
 use sov_stf_runner::{RollupConfig, RpcConfig, RunnerConfig, StorageConfig};
 use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::RuntimeCall;
 use tokio::sync::oneshot;
 
 pub async fn start_rollup(
@@ -18,11 +31,102 @@ pub async fn start_rollup(
     rollup_prover_config: RollupProverConfig,
 ) {
     let temp_dir = tempfile::tempdir().unwrap();
-    let temp_path = temp_dir.path();
 
-    let rollup_config = RollupConfig {
+    start_rollup_with_storage(
+        rpc_reporting_channel,
+        rt_genesis_paths,
+        kernel_genesis_paths,
+        rollup_prover_config,
+        temp_dir.path(),
+    )
+    .await;
+
+    // Close the tempdir explicitly to ensure that rustc doesn't see that it's unused and drop it unexpectedly
+    temp_dir.close().unwrap();
+}
+
+/// Starts a rollup storing its state in `storage_path`, which outlives the rollup.
+pub async fn start_rollup_with_storage(
+    rpc_reporting_channel: oneshot::Sender<SocketAddr>,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: BasicKernelGenesisPaths,
+    rollup_prover_config: RollupProverConfig,
+    storage_path: &Path,
+) {
+    let rollup_config = rollup_config(storage_path, mock_da_settings(None));
+    let mut mock_rollup = MockRollup::default();
+    mock_rollup.open_da_service(&rollup_config).await.unwrap();
+    let rollup = mock_rollup
+        .create_new_rollup(
+            &rt_genesis_paths,
+            read_kernel_genesis(&kernel_genesis_paths),
+            rollup_config,
+            rollup_prover_config,
+        )
+        .await
+        .unwrap();
+    rollup
+        .run_and_report_rpc_port(Some(rpc_reporting_channel))
+        .await
+        .unwrap();
+}
+
+/// Runs a rollup storing its state in `storage_path`, with the mock DA layer `da`, until
+/// `shutdown_signal` is received, then shuts it down gracefully.
+pub async fn run_rollup_until_shutdown(
+    rpc_reporting_channel: oneshot::Sender<SocketAddr>,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: BasicKernelGenesisPaths,
+    rollup_prover_config: RollupProverConfig,
+    storage_path: &Path,
+    da: MockDaSettings,
+    shutdown_signal: oneshot::Receiver<()>,
+) -> Result<(), anyhow::Error> {
+    let rollup_config = rollup_config(storage_path, da);
+    let rpc_config = rollup_config.runner.rpc_config.clone();
+    let mut mock_rollup = MockRollup::default();
+    mock_rollup.open_da_service(&rollup_config).await?;
+    let rollup = mock_rollup
+        .create_new_rollup(
+            &rt_genesis_paths,
+            read_kernel_genesis(&kernel_genesis_paths),
+            rollup_config,
+            rollup_prover_config,
+        )
+        .await?;
+    run_until_shutdown(
+        rollup,
+        &rpc_config,
+        Some(rpc_reporting_channel),
+        &mock_rollup.shutdown(),
+        async {
+            let _ = shutdown_signal.await;
+        },
+        Duration::from_secs(30),
+    )
+    .await?;
+    Ok(())
+}
+
+/// The mock DA layer of the test nodes, stored in `da_path` if any, producing a final block per
+/// submission.
+pub fn mock_da_settings(da_path: Option<&Path>) -> MockDaSettings {
+    MockDaSettings {
+        sender_address: MockAddress::from([0; 32]),
+        db_path: da_path.map(Path::to_path_buf),
+        block_time_ms: None,
+        produce_empty_blocks: false,
+        max_blob_size: None,
+        blocks_to_finality: 0,
+        reorg_depth: None,
+        reorg_interval: 10,
+    }
+}
+
+fn rollup_config(storage_path: &Path, da: MockDaSettings) -> RollupConfig<MockDaSettings> {
+    RollupConfig {
         storage: StorageConfig {
-            path: temp_path.to_path_buf(),
+            path: storage_path.to_path_buf(),
         },
         runner: RunnerConfig {
             start_height: 1,
@@ -31,38 +135,198 @@ pub async fn start_rollup(
                 bind_port: 0,
             },
         },
-        da: MockDaConfig {
-            sender_address: MockAddress::from([0; 32]),
-        },
+        da,
         prover_service: ProverServiceConfig {
             aggregated_proof_block_jump: 1,
         },
-    };
-
-    let mock_rollup = MockRollup {};
+    }
+}
 
-    let kernel_genesis = BasicKernelGenesisConfig {
+fn read_kernel_genesis(
+    kernel_genesis_paths: &BasicKernelGenesisPaths,
+) -> BasicKernelGenesisConfig<DefaultContext, MockDaSpec> {
+    BasicKernelGenesisConfig {
         chain_state: serde_json::from_str(
             &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
                 .expect("Failed to read chain_state genesis config"),
         )
         .expect("Failed to parse chain_state genesis config"),
-    };
+    }
+}
 
-    let rollup = mock_rollup
-        .create_new_rollup(
-            &rt_genesis_paths,
-            kernel_genesis,
-            rollup_config,
-            rollup_prover_config,
+/// A node running in its own runtime, like a node process: stopping it drops every task it
+/// spawned, closing its databases.
+pub struct Node {
+    pub rpc_address: SocketAddr,
+    shutdown_signal: oneshot::Sender<()>,
+    thread: std::thread::JoinHandle<Result<(), anyhow::Error>>,
+}
+
+impl Node {
+    pub async fn start(storage_path: &Path, da_path: &Path) -> Result<Self, anyhow::Error> {
+        Self::start_with_da(storage_path, mock_da_settings(Some(da_path))).await
+    }
+
+    /// Starts a node with the mock DA layer `da`.
+    pub async fn start_with_da(
+        storage_path: &Path,
+        da: MockDaSettings,
+    ) -> Result<Self, anyhow::Error> {
+        let storage_path = storage_path.to_path_buf();
+        let (port_tx, port_rx) = oneshot::channel();
+        let (shutdown_signal, shutdown_rx) = oneshot::channel();
+
+        let thread = std::thread::spawn(move || {
+            tokio::runtime::Runtime::new()?.block_on(run_rollup_until_shutdown(
+                port_tx,
+                GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+                BasicKernelGenesisPaths {
+                    chain_state: "../../test-data/genesis/mock/chain_state.json".into(),
+                },
+                RollupProverConfig::Skip,
+                &storage_path,
+                da,
+                shutdown_rx,
+            ))
+        });
+
+        Ok(Self {
+            rpc_address: port_rx.await?,
+            shutdown_signal,
+            thread,
+        })
+    }
+
+    pub async fn stop(self) -> Result<(), anyhow::Error> {
+        let _ = self.shutdown_signal.send(());
+        tokio::task::spawn_blocking(move || self.thread.join())
+            .await?
+            .map_err(|_| anyhow::anyhow!("The node panicked"))?
+    }
+}
+
+/// Runs a rollup with `rt_genesis_paths` and the mock kernel genesis until `test` completes,
+/// passing it the RPC address. Returns the error of the rollup if it stops first.
+pub async fn with_rollup<Fut>(
+    rt_genesis_paths: GenesisPaths,
+    test: impl FnOnce(SocketAddr) -> Fut,
+) -> Result<(), anyhow::Error>
+where
+    Fut: Future<Output = Result<(), anyhow::Error>>,
+{
+    let (port_tx, port_rx) = oneshot::channel();
+    let rollup_task = tokio::spawn(start_rollup(
+        port_tx,
+        rt_genesis_paths,
+        BasicKernelGenesisPaths {
+            chain_state: "../../test-data/genesis/mock/chain_state.json".into(),
+        },
+        RollupProverConfig::Execute,
+    ));
+
+    let port = port_rx.await?;
+    tokio::select! {
+        err = rollup_task => err?,
+        res = test(port) => res?,
+    }
+    Ok(())
+}
+
+/// Copies the mock genesis to `dir`.
+pub fn copy_mock_genesis(dir: &Path) -> Result<(), anyhow::Error> {
+    let mock_genesis = Path::new("../../test-data/genesis/mock/");
+    for entry in std::fs::read_dir(mock_genesis)? {
+        let entry = entry?;
+        std::fs::copy(entry.path(), dir.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Rewrites the JSON file at `path` with `update`.
+pub fn update_json(
+    path: &Path,
+    update: impl FnOnce(&mut serde_json::Value),
+) -> Result<(), anyhow::Error> {
+    let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    update(&mut value);
+    std::fs::write(path, value.to_string())?;
+    Ok(())
+}
+
+#[derive(serde::Deserialize)]
+struct KeyFile {
+    private_key: DefaultPrivateKey,
+}
+
+/// The key of the deployer, who holds both tokens of the mock genesis.
+pub fn deployer_key() -> Result<DefaultPrivateKey, anyhow::Error> {
+    let key_file: KeyFile = serde_json::from_str(&std::fs::read_to_string(
+        "../../test-data/keys/token_deployer_private_key.json",
+    )?)?;
+    Ok(key_file.private_key)
+}
+
+/// Subscribes to the slots processed by the rollup.
+pub async fn subscribe_slots(client: &SimpleClient) -> Result<Subscription<u64>, anyhow::Error> {
+    Ok(client
+        .ws()
+        .subscribe(
+            "ledger_subscribeSlots",
+            rpc_params![],
+            "ledger_unsubscribeSlots",
         )
-        .await
-        .unwrap();
-    rollup
-        .run_and_report_rpc_port(Some(rpc_reporting_channel))
-        .await
-        .unwrap();
+        .await?)
+}
 
-    // Close the tempdir explicitly to ensure that rustc doesn't see that it's unused and drop it unexpectedly
-    temp_dir.close().unwrap();
+/// A bank call creating the token `token_name`, minting 1000 tokens to the signer of `key`.
+pub fn create_token_call(
+    key: &DefaultPrivateKey,
+    token_name: &str,
+    salt: u64,
+) -> RuntimeCall<DefaultContext, MockDaSpec> {
+    RuntimeCall::bank(sov_bank::CallMessage::CreateToken {
+        salt,
+        token_name: token_name.to_string(),
+        initial_balance: 1000,
+        minter_address: key.to_address(),
+        authorized_minters: vec![],
+    })
+}
+
+/// Signs `msg` with `key`, without gas.
+pub fn signed_tx(
+    key: &DefaultPrivateKey,
+    msg: RuntimeCall<DefaultContext, MockDaSpec>,
+    nonce: u64,
+) -> Transaction<DefaultContext> {
+    let chain_id = 0;
+    let gas_tip = 0;
+    let gas_limit = 0;
+    Transaction::<DefaultContext>::new_signed_tx(
+        key,
+        msg.try_to_vec().unwrap(),
+        chain_id,
+        gas_tip,
+        gas_limit,
+        nonce,
+    )
+}
+
+/// Creates a token, returning the height of the slot that included it.
+pub async fn send_create_token(
+    rpc_address: SocketAddr,
+    key: &DefaultPrivateKey,
+    nonce: u64,
+) -> Result<u64, anyhow::Error> {
+    let client = SimpleClient::new("localhost", rpc_address.port()).await?;
+    let mut slot_processed_subscription = subscribe_slots(&client).await?;
+
+    let msg = create_token_call(key, "test_token", nonce);
+    client.send_transaction(signed_tx(key, msg, nonce)).await?;
+
+    let height = slot_processed_subscription
+        .next()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("The rollup stopped"))??;
+    Ok(height)
 }
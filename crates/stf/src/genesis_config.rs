@@ -1,41 +1,166 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context as _};
+use chain_history::ChainHistoryConfig;
+use sequencer_rewards::SequencerRewardsConfig;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use sov_accounts::AccountConfig;
 use sov_bank::BankConfig;
-use sov_modules_api::{Context, DaSpec};
+#[cfg(feature = "experimental")]
+use sov_evm::EvmConfig;
+use sov_modules_api::macros::config_constant;
+use sov_modules_api::{Context, DaSpec, PublicKey};
 use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
 use sov_sequencer_registry::SequencerConfig;
-use sov_stf_runner::read_json_file;
+use sov_stf_runner::{from_toml_path, read_json_file};
+use tx_fees::TxFeesConfig;
 
 use super::GenesisConfig;
 use crate::Runtime;
 
-/// Paths to genesis files.
-pub struct GenesisPaths {
+/// The token used to pay for gas and sequencer rewards.
+#[config_constant]
+pub const GAS_TOKEN_ADDRESS: &'static str;
+
+/// Where the genesis configuration is read from.
+pub enum GenesisPaths {
+    /// One JSON file per module.
+    Modules(ModuleGenesisPaths),
+    /// A single `genesis.json` or `genesis.toml` file, with a section per module named after
+    /// the `Runtime` field, and the kernel `chain_state`.
+    File(PathBuf),
+}
+
+impl GenesisPaths {
+    /// Creates a new [`GenesisPaths`] from the files contained in the given
+    /// directory.
+    ///
+    /// Take a look at the contents of the `test_data` directory to see the
+    /// expected files.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Self {
+        Self::Modules(ModuleGenesisPaths::from_dir(dir))
+    }
+
+    /// Creates a new [`GenesisPaths`] from a single genesis file.
+    pub fn from_file(path: impl AsRef<Path>) -> Self {
+        Self::File(path.as_ref().to_path_buf())
+    }
+
+    /// Creates a new [`GenesisPaths`] from either a directory or a single genesis file.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        if path.as_ref().is_dir() {
+            Self::from_dir(path)
+        } else {
+            Self::from_file(path)
+        }
+    }
+
+    /// Reads the genesis of the `sequencer_registry` module.
+    pub fn read_sequencer_registry_config<C: Context, Da: DaSpec>(
+        &self,
+    ) -> anyhow::Result<SequencerConfig<C, Da>> {
+        match self {
+            GenesisPaths::Modules(paths) => read_json_file(&paths.sequencer_genesis_path),
+            GenesisPaths::File(path) => {
+                let file: SequencerRegistrySection<C, Da> = read_genesis_file(path)?;
+                Ok(file.sequencer_registry)
+            }
+        }
+    }
+
+    /// Reads the kernel `chain_state` section of a single genesis file.
+    ///
+    /// Returns `None` for the directory layout, whose chain state is a separate file.
+    pub fn read_chain_state_config(&self) -> anyhow::Result<Option<serde_json::Value>> {
+        match self {
+            GenesisPaths::Modules(_) => Ok(None),
+            GenesisPaths::File(path) => {
+                let file: ChainStateSection = read_genesis_file(path)?;
+                Ok(Some(file.chain_state))
+            }
+        }
+    }
+}
+
+/// Paths to the genesis files of each module.
+pub struct ModuleGenesisPaths {
     /// Accounts genesis path.
     pub accounts_genesis_path: PathBuf,
     /// Bank genesis path.
     pub bank_genesis_path: PathBuf,
     /// Sequencer Registry genesis path.
     pub sequencer_genesis_path: PathBuf,
+    /// Sequencer Rewards genesis path.
+    pub sequencer_rewards_genesis_path: PathBuf,
+    /// Tx Fees genesis path.
+    pub tx_fees_genesis_path: PathBuf,
+    /// Chain History genesis path.
+    pub chain_history_genesis_path: PathBuf,
+    /// EVM genesis path.
+    #[cfg(feature = "experimental")]
+    pub evm_genesis_path: PathBuf,
 }
 
-impl GenesisPaths {
-    /// Creates a new [`GenesisPaths`] from the files contained in the given
+impl ModuleGenesisPaths {
+    /// Creates a new [`ModuleGenesisPaths`] from the files contained in the given
     /// directory.
-    ///
-    /// Take a look at the contents of the `test_data` directory to see the
-    /// expected files.
     pub fn from_dir(dir: impl AsRef<Path>) -> Self {
         Self {
             accounts_genesis_path: dir.as_ref().join("accounts.json"),
             bank_genesis_path: dir.as_ref().join("bank.json"),
             sequencer_genesis_path: dir.as_ref().join("sequencer_registry.json"),
+            sequencer_rewards_genesis_path: dir.as_ref().join("sequencer_rewards.json"),
+            tx_fees_genesis_path: dir.as_ref().join("tx_fees.json"),
+            chain_history_genesis_path: dir.as_ref().join("chain_history.json"),
+            #[cfg(feature = "experimental")]
+            evm_genesis_path: dir.as_ref().join("evm.json"),
         }
     }
 }
 
+/// The module sections of a single genesis file.
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "AccountConfig<C>: DeserializeOwned, \
+    BankConfig<C>: DeserializeOwned, \
+    SequencerConfig<C, Da>: DeserializeOwned, \
+    SequencerRewardsConfig<C, Da>: DeserializeOwned, \
+    TxFeesConfig<C>: DeserializeOwned"))]
+struct GenesisFile<C: Context, Da: DaSpec> {
+    accounts: AccountConfig<C>,
+    bank: BankConfig<C>,
+    sequencer_registry: SequencerConfig<C, Da>,
+    sequencer_rewards: SequencerRewardsConfig<C, Da>,
+    tx_fees: TxFeesConfig<C>,
+    chain_history: ChainHistoryConfig,
+    #[cfg(feature = "experimental")]
+    evm: EvmConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "SequencerConfig<C, Da>: DeserializeOwned"))]
+struct SequencerRegistrySection<C: Context, Da: DaSpec> {
+    sequencer_registry: SequencerConfig<C, Da>,
+}
+
+#[derive(Deserialize)]
+struct ChainStateSection {
+    chain_state: serde_json::Value,
+}
+
+/// Reads a single genesis file, in TOML if its extension is `.toml` and in JSON otherwise.
+fn read_genesis_file<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        from_toml_path(path)
+    } else {
+        read_json_file(path)
+    }
+}
+
 /// Creates genesis configuration.
 pub(crate) fn get_genesis_config<C: Context, Da: DaSpec>(
     genesis_paths: &GenesisPaths,
@@ -46,41 +171,212 @@ pub(crate) fn get_genesis_config<C: Context, Da: DaSpec>(
     validate_config(genesis_config)
 }
 
+/// Checks the consistency of the genesis configuration, reporting every problem at once.
 fn validate_config<C: Context, Da: DaSpec>(
     genesis_config: <Runtime<C, Da> as RuntimeTrait<C, Da>>::GenesisConfig,
 ) -> Result<<Runtime<C, Da> as RuntimeTrait<C, Da>>::GenesisConfig, anyhow::Error> {
-    let token_address = &sov_bank::get_genesis_token_address::<C>(
-        &genesis_config.bank.tokens[0].token_name,
-        genesis_config.bank.tokens[0].salt,
-    );
+    let mut problems = Vec::new();
 
-    let coins_token_addr = &genesis_config
-        .sequencer_registry
-        .coins_to_lock
-        .token_address;
+    check_bank(&genesis_config, &mut problems);
+    check_sequencer(&genesis_config, &mut problems);
+    check_reward_pool(&genesis_config, &mut problems);
 
-    if coins_token_addr != token_address {
+    let reward_token_addr = &genesis_config.sequencer_rewards.reward_token_address;
+    if reward_token_addr.to_string() != GAS_TOKEN_ADDRESS {
+        problems.push(format!(
+            "Wrong token address in `sequencer_rewards_config` expected {} but found {}",
+            GAS_TOKEN_ADDRESS, reward_token_addr
+        ));
+    }
+
+    let gas_token_addr = &genesis_config.tx_fees.gas_token_address;
+    if gas_token_addr.to_string() != GAS_TOKEN_ADDRESS {
+        problems.push(format!(
+            "Wrong token address in `tx_fees_config` expected {} but found {}",
+            GAS_TOKEN_ADDRESS, gas_token_addr
+        ));
+    }
+
+    if !problems.is_empty() {
         bail!(
-            "Wrong token address in `sequencer_registry_config` expected {} but found {}",
-            token_address,
-            coins_token_addr
-        )
+            "Invalid genesis configuration:\n  - {}",
+            problems.join("\n  - ")
+        );
     }
 
     Ok(genesis_config)
 }
 
+/// Checks the tokens of the `bank` genesis.
+///
+/// Tokens are identified by their address, derived from their name and salt. Authorized minters
+/// must be registered in `accounts`.
+fn check_bank<C: Context, Da: DaSpec>(
+    genesis_config: &GenesisConfig<C, Da>,
+    problems: &mut Vec<String>,
+) {
+    let tokens = &genesis_config.bank.tokens;
+    if tokens.is_empty() {
+        problems.push("No token defined in `bank_config`".to_owned());
+    }
+
+    let accounts: HashSet<C::Address> = genesis_config
+        .accounts
+        .pub_keys
+        .iter()
+        .map(|pub_key| pub_key.to_address::<C::Address>())
+        .collect();
+
+    let mut token_addresses = HashSet::new();
+    for token in tokens {
+        let token_address = sov_bank::get_genesis_token_address::<C>(&token.token_name, token.salt);
+        if !token_addresses.insert(token_address) {
+            problems.push(format!(
+                "Token `{}` with salt {} is defined more than once in `bank_config`",
+                token.token_name, token.salt
+            ));
+        }
+
+        let mut holders = HashSet::new();
+        let mut supply = Some(0u64);
+        for (address, balance) in &token.address_and_balances {
+            if !holders.insert(address) {
+                problems.push(format!(
+                    "Address {} holds token `{}` more than once in `bank_config`",
+                    address, token.token_name
+                ));
+            }
+            supply = supply.and_then(|supply| supply.checked_add(*balance));
+        }
+        if supply.is_none() {
+            problems.push(format!(
+                "The total supply of token `{}` overflows in `bank_config`",
+                token.token_name
+            ));
+        }
+
+        for minter in &token.authorized_minters {
+            if !accounts.contains(minter) {
+                problems.push(format!(
+                    "Minter {} of token `{}` is not in `accounts_config`",
+                    minter, token.token_name
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that the reward pool is a separate account funded with the reward token.
+fn check_reward_pool<C: Context, Da: DaSpec>(
+    genesis_config: &GenesisConfig<C, Da>,
+    problems: &mut Vec<String>,
+) {
+    let rewards = &genesis_config.sequencer_rewards;
+    if rewards.reward_pool_address == genesis_config.sequencer_registry.seq_rollup_address {
+        problems.push(format!(
+            "The reward pool {} is the rollup address of the sequencer, rewards would not be paid",
+            rewards.reward_pool_address
+        ));
+    }
+
+    let reward_token = genesis_config.bank.tokens.iter().find(|token| {
+        sov_bank::get_genesis_token_address::<C>(&token.token_name, token.salt)
+            == rewards.reward_token_address
+    });
+    let Some(reward_token) = reward_token else {
+        problems.push(format!(
+            "Wrong token address in `sequencer_rewards_config`: {} is not a genesis token",
+            rewards.reward_token_address
+        ));
+        return;
+    };
+
+    let funded = reward_token
+        .address_and_balances
+        .iter()
+        .any(|(address, balance)| address == &rewards.reward_pool_address && *balance > 0);
+    if !funded {
+        problems.push(format!(
+            "The reward pool {} holds no `{}` at genesis",
+            rewards.reward_pool_address, reward_token.token_name
+        ));
+    }
+}
+
+/// Checks that the genesis sequencer can lock its coins in a genesis token.
+fn check_sequencer<C: Context, Da: DaSpec>(
+    genesis_config: &GenesisConfig<C, Da>,
+    problems: &mut Vec<String>,
+) {
+    let sequencer = &genesis_config.sequencer_registry;
+    let coins_to_lock = &sequencer.coins_to_lock;
+
+    let lock_token = genesis_config.bank.tokens.iter().find(|token| {
+        sov_bank::get_genesis_token_address::<C>(&token.token_name, token.salt)
+            == coins_to_lock.token_address
+    });
+
+    let Some(lock_token) = lock_token else {
+        problems.push(format!(
+            "Wrong token address in `sequencer_registry_config`: {} is not a genesis token",
+            coins_to_lock.token_address
+        ));
+        return;
+    };
+
+    let balance: u64 = lock_token
+        .address_and_balances
+        .iter()
+        .filter(|(address, _)| address == &sequencer.seq_rollup_address)
+        .map(|(_, balance)| *balance)
+        .fold(0, u64::saturating_add);
+    if balance < coins_to_lock.amount {
+        problems.push(format!(
+            "Sequencer {} needs {} of token `{}` to register, but only holds {}",
+            sequencer.seq_rollup_address, coins_to_lock.amount, lock_token.token_name, balance
+        ));
+    }
+}
+
 fn create_genesis_config<C: Context, Da: DaSpec>(
     genesis_paths: &GenesisPaths,
 ) -> anyhow::Result<GenesisConfig<C, Da>> {
-    let accounts_config: AccountConfig<C> = read_json_file(&genesis_paths.accounts_genesis_path)?;
-    let bank_config: BankConfig<C> = read_json_file(&genesis_paths.bank_genesis_path)?;
-    let sequencer_registry_config: SequencerConfig<C, Da> =
-        read_json_file(&genesis_paths.sequencer_genesis_path)?;
+    let file = match genesis_paths {
+        GenesisPaths::Modules(paths) => read_module_genesis_files(paths)?,
+        GenesisPaths::File(path) => read_genesis_file(path)?,
+    };
+
+    // The genesis sequencer is paid to the rollup address it registers with
+    let mut sequencer_rewards = file.sequencer_rewards;
+    sequencer_rewards.payout_addresses = vec![(
+        file.sequencer_registry.seq_da_address.clone(),
+        file.sequencer_registry.seq_rollup_address.clone(),
+    )];
 
     Ok(GenesisConfig::new(
-        accounts_config,
-        bank_config,
-        sequencer_registry_config,
+        file.accounts,
+        file.bank,
+        file.sequencer_registry,
+        sequencer_rewards,
+        file.tx_fees,
+        file.chain_history,
+        (),
+        #[cfg(feature = "experimental")]
+        file.evm,
     ))
 }
+
+fn read_module_genesis_files<C: Context, Da: DaSpec>(
+    genesis_paths: &ModuleGenesisPaths,
+) -> anyhow::Result<GenesisFile<C, Da>> {
+    Ok(GenesisFile {
+        accounts: read_json_file(&genesis_paths.accounts_genesis_path)?,
+        bank: read_json_file(&genesis_paths.bank_genesis_path)?,
+        sequencer_registry: read_json_file(&genesis_paths.sequencer_genesis_path)?,
+        sequencer_rewards: read_json_file(&genesis_paths.sequencer_rewards_genesis_path)?,
+        tx_fees: read_json_file(&genesis_paths.tx_fees_genesis_path)?,
+        chain_history: read_json_file(&genesis_paths.chain_history_genesis_path)?,
+        #[cfg(feature = "experimental")]
+        evm: read_json_file(&genesis_paths.evm_genesis_path)?,
+    })
+}
@@ -5,12 +5,18 @@
 //! - At the beginning and end of each slot (DA layer block)
 
 use super::runtime::Runtime;
+use super::runtime::RuntimeCall;
+use indexer::TokenEvent;
 use sov_accounts::AccountsTxHook;
 use sov_bank::BankTxHook;
+use sov_bank::Coins;
 use sov_modules_api::hooks::{ApplyBlobHooks, FinalizeHook, SlotHooks, TxHooks};
 use sov_modules_api::transaction::Transaction;
+use sov_modules_api::DispatchCall;
 use sov_modules_api::{AccessoryWorkingSet, BlobReaderTrait, Context, DaSpec, Spec, WorkingSet};
-use sov_modules_stf_blueprint::{RuntimeTxHook, SequencerOutcome};
+use sov_modules_stf_blueprint::{RuntimeTxHook, SequencerOutcome, SlashingReason};
+#[cfg(any(feature = "native", feature = "experimental"))]
+use sov_rollup_interface::da::BlockHeaderTrait;
 use sov_sequencer_registry::SequencerRegistry;
 use sov_state::Storage;
 use tracing::info;
@@ -33,6 +39,17 @@ impl<C: Context, Da: DaSpec> TxHooks for Runtime<C, Da> {
 
         let hook = BankTxHook { sender, sequencer };
         self.bank.pre_dispatch_tx_hook(tx, working_set, &hook)?;
+        self.tx_fees.reserve_gas(tx, &hook.sender, working_set)?;
+        self.begin_registration(tx, &hook.sender, working_set);
+        // Read in the zk execution as well, so both executions read the same state
+        #[cfg_attr(not(feature = "native"), allow(unused_variables))]
+        let expected_events = self.indexer.expected_events(
+            self.token_events(tx, &hook.sender),
+            &self.bank,
+            working_set,
+        );
+        #[cfg(feature = "native")]
+        self.indexer.begin_tx(expected_events, working_set);
 
         Ok(C::new(hook.sender, hook.sequencer, *height))
     }
@@ -43,9 +60,95 @@ impl<C: Context, Da: DaSpec> TxHooks for Runtime<C, Da> {
         ctx: &C,
         working_set: &mut WorkingSet<C>,
     ) -> anyhow::Result<()> {
+        // Before the fees are settled, so the compared balances were only changed by the call
+        #[cfg(feature = "native")]
+        self.indexer.index_tx(tx, ctx, &self.bank, working_set);
+        self.sequencer_rewards.end_registration(working_set);
+
         self.accounts.post_dispatch_tx_hook(tx, ctx, working_set)?;
         self.bank.post_dispatch_tx_hook(tx, ctx, working_set)?;
-        Ok(())
+        self.tx_fees.settle_gas(tx, ctx, working_set)
+    }
+}
+
+impl<C: Context, Da: DaSpec> Runtime<C, Da> {
+    /// Reports the sequencer registry call of `tx` to the sequencer rewards, so the payout
+    /// addresses follow the registrations and exits that take effect.
+    fn begin_registration(
+        &self,
+        tx: &Transaction<C>,
+        sender: &C::Address,
+        working_set: &mut WorkingSet<C>,
+    ) {
+        let Ok(RuntimeCall::sequencer_registry(call)) =
+            <Self as DispatchCall>::decode_call(tx.runtime_msg())
+        else {
+            return;
+        };
+        let (da_address, exit) = match call {
+            sov_sequencer_registry::CallMessage::Register { da_address } => (da_address, false),
+            sov_sequencer_registry::CallMessage::Exit { da_address } => (da_address, true),
+        };
+        // The registry rejects the calls with an invalid DA address
+        let Ok(sequencer_da_address) = Da::Address::try_from(&da_address[..]) else {
+            return;
+        };
+        let Some(locked) = self.sequencer_registry.get_coins_to_lock(working_set) else {
+            return;
+        };
+        self.sequencer_rewards.begin_registration(
+            sequencer_da_address,
+            sender.clone(),
+            exit,
+            locked,
+            working_set,
+        );
+    }
+
+    /// The token balance changes requested by the bank call of `tx`, for the indexer.
+    fn token_events(&self, tx: &Transaction<C>, sender: &C::Address) -> Vec<TokenEvent<C>> {
+        let Ok(call) = <Self as DispatchCall>::decode_call(tx.runtime_msg()) else {
+            return Vec::new();
+        };
+
+        match call {
+            // A transfer to the sender changes no balance, so its outcome cannot be observed
+            RuntimeCall::bank(sov_bank::CallMessage::Transfer { to, .. }) if to == *sender => {
+                Vec::new()
+            }
+            RuntimeCall::bank(sov_bank::CallMessage::Transfer { to, coins }) => {
+                vec![TokenEvent::Transfer {
+                    from: sender.clone(),
+                    to,
+                    coins,
+                }]
+            }
+            RuntimeCall::bank(sov_bank::CallMessage::CreateToken {
+                salt,
+                token_name,
+                initial_balance,
+                minter_address,
+                ..
+            }) => vec![TokenEvent::Mint {
+                to: minter_address,
+                coins: Coins {
+                    amount: initial_balance,
+                    token_address: sov_bank::get_token_address::<C>(
+                        &token_name,
+                        sender.as_ref(),
+                        salt,
+                    ),
+                },
+            }],
+            RuntimeCall::bank(sov_bank::CallMessage::Mint {
+                coins,
+                minter_address,
+            }) => vec![TokenEvent::Mint {
+                to: minter_address,
+                coins,
+            }],
+            _ => Vec::new(),
+        }
     }
 }
 
@@ -59,8 +162,14 @@ impl<C: Context, Da: DaSpec> ApplyBlobHooks<Da::BlobTransaction> for Runtime<C,
         blob: &mut Da::BlobTransaction,
         working_set: &mut WorkingSet<C>,
     ) -> anyhow::Result<()> {
+        #[cfg(feature = "native")]
+        crate::spans::enter_batch(blob.hash(), &blob.sender());
+
         // Before executing each batch, check that the sender is regsitered as a sequencer
-        self.sequencer_registry.begin_blob_hook(blob, working_set)
+        self.sequencer_registry.begin_blob_hook(blob, working_set)?;
+        self.sequencer_rewards
+            .begin_blob_hook(&blob.sender(), working_set);
+        Ok(())
     }
 
     fn end_blob_hook(
@@ -69,21 +178,33 @@ impl<C: Context, Da: DaSpec> ApplyBlobHooks<Da::BlobTransaction> for Runtime<C,
         working_set: &mut WorkingSet<C>,
     ) -> anyhow::Result<()> {
         // After processing each blob, reward or slash the sequencer if appropriate
-        match result {
-            SequencerOutcome::Rewarded(_reward) => {
-                // TODO: Process reward here or above.
+        let result = match result {
+            SequencerOutcome::Rewarded(reward) => {
+                self.sequencer_rewards.reward_sequencer(reward, working_set);
                 <SequencerRegistry<C, Da> as ApplyBlobHooks<Da::BlobTransaction>>::end_blob_hook(
                     &self.sequencer_registry,
                     sov_sequencer_registry::SequencerOutcome::Completed,
                     working_set,
                 )
             }
-            SequencerOutcome::Ignored => Ok(()),
+            SequencerOutcome::Ignored => {
+                self.sequencer_rewards.end_blob_hook(working_set);
+                Ok(())
+            }
             SequencerOutcome::Slashed {
                 reason,
                 sequencer_da_address,
             } => {
                 info!("Sequencer {} slashed: {:?}", sequencer_da_address, reason);
+                let forfeited = self.sequencer_registry.get_coins_to_lock(working_set);
+                let height = self.chain_history.get_current_height(working_set);
+                self.sequencer_rewards.slash_sequencer(
+                    &sequencer_da_address,
+                    slash_reason(reason),
+                    height,
+                    forfeited,
+                    working_set,
+                );
                 <SequencerRegistry<C, Da> as ApplyBlobHooks<Da::BlobTransaction>>::end_blob_hook(
                     &self.sequencer_registry,
                     sov_sequencer_registry::SequencerOutcome::Slashed {
@@ -92,6 +213,25 @@ impl<C: Context, Da: DaSpec> ApplyBlobHooks<Da::BlobTransaction> for Runtime<C,
                     working_set,
                 )
             }
+        };
+
+        #[cfg(feature = "native")]
+        crate::spans::exit_batch();
+        result
+    }
+}
+
+/// The reason recorded by the sequencer rewards for a slashing reason of the STF.
+fn slash_reason(reason: SlashingReason) -> sequencer_rewards::SlashReason {
+    match reason {
+        SlashingReason::InvalidBatchEncoding => {
+            sequencer_rewards::SlashReason::InvalidBatchEncoding
+        }
+        SlashingReason::StatelessVerificationFailed => {
+            sequencer_rewards::SlashReason::StatelessVerificationFailed
+        }
+        SlashingReason::InvalidTransactionEncoding => {
+            sequencer_rewards::SlashReason::InvalidTransactionEncoding
         }
     }
 }
@@ -101,23 +241,49 @@ impl<C: Context, Da: DaSpec> SlotHooks<Da> for Runtime<C, Da> {
 
     fn begin_slot_hook(
         &self,
-        _slot_header: &Da::BlockHeader,
+        slot_header: &Da::BlockHeader,
         _validity_condition: &Da::ValidityCondition,
-        _pre_state_root: &<<Self::Context as Spec>::Storage as Storage>::Root,
-        _working_set: &mut sov_modules_api::WorkingSet<C>,
+        pre_state_root: &<<Self::Context as Spec>::Storage as Storage>::Root,
+        working_set: &mut sov_modules_api::WorkingSet<C>,
     ) {
+        self.chain_history
+            .begin_slot_hook::<Da>(slot_header, pre_state_root, working_set);
+
+        // The height was just written, so reading it does not change the witness of the slot
+        #[cfg(feature = "native")]
+        crate::spans::enter_slot(
+            self.chain_history.get_current_height(working_set),
+            slot_header.height(),
+        );
+
+        #[cfg(feature = "experimental")]
+        self.evm
+            .begin_slot_hook(slot_header.hash().into(), pre_state_root, working_set);
     }
 
-    fn end_slot_hook(&self, _working_set: &mut sov_modules_api::WorkingSet<C>) {}
+    #[allow(unused_variables)]
+    fn end_slot_hook(&self, working_set: &mut sov_modules_api::WorkingSet<C>) {
+        #[cfg(feature = "experimental")]
+        self.evm.end_slot_hook(working_set);
+
+        #[cfg(feature = "native")]
+        crate::spans::exit_slot();
+    }
 }
 
 impl<C: Context, Da: sov_modules_api::DaSpec> FinalizeHook<Da> for Runtime<C, Da> {
     type Context = C;
 
+    #[allow(unused_variables)]
     fn finalize_hook(
         &self,
-        _root_hash: &<<Self::Context as Spec>::Storage as Storage>::Root,
-        _accessory_working_set: &mut AccessoryWorkingSet<C>,
+        root_hash: &<<Self::Context as Spec>::Storage as Storage>::Root,
+        accessory_working_set: &mut AccessoryWorkingSet<C>,
     ) {
+        #[cfg(feature = "native")]
+        self.indexer.finalize_hook(accessory_working_set);
+
+        #[cfg(feature = "experimental")]
+        self.evm.finalize_hook(root_hash, accessory_working_set);
     }
 }
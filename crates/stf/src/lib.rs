@@ -4,7 +4,10 @@
 pub mod genesis_config;
 mod hooks;
 pub mod runtime;
+#[cfg(feature = "native")]
+mod spans;
 pub use runtime::*;
+use sov_modules_api::macros::config_constant;
 use sov_modules_stf_blueprint::StfBlueprint;
 use sov_rollup_interface::da::DaVerifier;
 use sov_stf_runner::verifier::StateTransitionVerifier;
@@ -14,3 +17,70 @@ pub type StfVerifier<DA, Vm, ZkContext, RT, K> =
     StateTransitionVerifier<StfBlueprint<ZkContext, <DA as DaVerifier>::Spec, Vm, RT, K>, DA, Vm>;
 
 pub use sov_mock_da::MockDaSpec;
+
+/// The Celestia namespace the rollup batches are posted to.
+///
+/// Set in `constants.json`, which is the single source of truth for both the node and the
+/// celestia guest. Rollups sharing a Celestia network must use distinct namespaces.
+#[config_constant]
+pub const ROLLUP_BATCH_NAMESPACE: &'static str;
+
+/// The Celestia namespace the rollup proofs are posted to. Set in `constants.json`.
+#[config_constant]
+pub const ROLLUP_PROOF_NAMESPACE: &'static str;
+
+/// Converts a namespace from `constants.json` into a 10 bytes Celestia namespace id.
+///
+/// Fails to compile if the namespace is not exactly 10 bytes long.
+pub const fn namespace_id(namespace: &str) -> [u8; 10] {
+    let bytes = namespace.as_bytes();
+    assert!(
+        bytes.len() == 10,
+        "Celestia namespaces must be 10 bytes long"
+    );
+
+    let mut id = [0u8; 10];
+    let mut i = 0;
+    while i < 10 {
+        id[i] = bytes[i];
+        i += 1;
+    }
+    id
+}
+
+/// Surrounds the batch namespace the celestia guest embeds in its ELF.
+const GUEST_NAMESPACE_MARKER: &[u8; 16] = b"ROLLUP_NAMESPACE";
+
+/// The length of the namespace record the celestia guest embeds in its ELF.
+pub const GUEST_NAMESPACE_RECORD_LEN: usize = 2 * GUEST_NAMESPACE_MARKER.len() + 10;
+
+/// The batch namespace between two markers, embedded by the celestia guest in its ELF so that
+/// the node can read back the namespace the guest was actually built with.
+pub const fn guest_namespace_record(namespace: &str) -> [u8; GUEST_NAMESPACE_RECORD_LEN] {
+    let id = namespace_id(namespace);
+    let marker_len = GUEST_NAMESPACE_MARKER.len();
+    let mut record = [0u8; GUEST_NAMESPACE_RECORD_LEN];
+    let mut i = 0;
+    while i < marker_len {
+        record[i] = GUEST_NAMESPACE_MARKER[i];
+        record[marker_len + 10 + i] = GUEST_NAMESPACE_MARKER[i];
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 10 {
+        record[marker_len + i] = id[i];
+        i += 1;
+    }
+    record
+}
+
+/// Reads back the batch namespace embedded by [`guest_namespace_record`] in a guest ELF, if any.
+pub fn read_guest_namespace(elf: &[u8]) -> Option<[u8; 10]> {
+    let marker_len = GUEST_NAMESPACE_MARKER.len();
+    elf.windows(GUEST_NAMESPACE_RECORD_LEN).find_map(|record| {
+        let (start, rest) = record.split_at(marker_len);
+        let (id, end) = rest.split_at(10);
+        (start == GUEST_NAMESPACE_MARKER && end == GUEST_NAMESPACE_MARKER)
+            .then(|| id.try_into().expect("The namespace id is 10 bytes long"))
+    })
+}
@@ -5,16 +5,26 @@
 //!   2. Add the module to the `Runtime` below
 //!   3. Update `genesis.json` with any additional data required by your new module
 
+#[cfg(feature = "native")]
+pub use chain_history::{ChainHistoryRpcImpl, ChainHistoryRpcServer};
+#[cfg(feature = "native")]
+pub use indexer::{IndexerRpcImpl, IndexerRpcServer};
+#[cfg(feature = "native")]
+pub use sequencer_rewards::{SequencerRewardsRpcImpl, SequencerRewardsRpcServer};
 #[cfg(feature = "native")]
 pub use sov_accounts::{AccountsRpcImpl, AccountsRpcServer};
 #[cfg(feature = "native")]
 pub use sov_bank::{BankRpcImpl, BankRpcServer};
+#[cfg(all(feature = "native", feature = "experimental"))]
+pub use sov_evm::{EvmRpcImpl, EvmRpcServer};
 use sov_modules_api::macros::DefaultRuntime;
 #[cfg(feature = "native")]
 use sov_modules_api::Spec;
 use sov_modules_api::{Context, DaSpec, DispatchCall, Genesis, MessageCodec};
 #[cfg(feature = "native")]
 pub use sov_sequencer_registry::{SequencerRegistryRpcImpl, SequencerRegistryRpcServer};
+#[cfg(feature = "native")]
+pub use tx_fees::{TxFeesRpcImpl, TxFeesRpcServer};
 
 #[cfg(feature = "native")]
 use crate::genesis_config::GenesisPaths;
@@ -64,6 +74,22 @@ pub struct Runtime<C: Context, Da: DaSpec> {
     pub bank: sov_bank::Bank<C>,
     /// The sequencer registry module is responsible for authorizing users to sequencer rollup transactions
     pub sequencer_registry: sov_sequencer_registry::SequencerRegistry<C, Da>,
+    /// The sequencer rewards module pays sequencers for the batches they submit
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub sequencer_rewards: sequencer_rewards::SequencerRewards<C, Da>,
+    /// The tx fees module charges the senders of transactions for the gas they use
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub tx_fees: tx_fees::TxFees<C>,
+    /// The chain history module records the DA block each slot was produced from
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub chain_history: chain_history::ChainHistory<C>,
+    /// The indexer module maintains explorer indexes in accessory state, on native nodes only
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub indexer: indexer::Indexer<C>,
+    /// The EVM module executes Ethereum transactions and exposes the `eth_*` queries
+    #[cfg_attr(feature = "native", cli_skip)]
+    #[cfg(feature = "experimental")]
+    pub evm: sov_evm::Evm<C>,
 }
 
 impl<C, Da> sov_modules_stf_blueprint::Runtime<C, Da> for Runtime<C, Da>
@@ -0,0 +1,48 @@
+//! The tracing spans the hooks open around the slots and the batches they process, so the logs
+//! of the modules can be correlated with the rollup height, the DA height and the batch hash.
+//!
+//! A slot is processed by a single synchronous call of the STF, so the hooks keep the entered
+//! spans in thread-local storage between the beginning and the end of a slot or a batch.
+
+use std::cell::RefCell;
+
+use tracing::span::EnteredSpan;
+
+thread_local! {
+    static SLOT_SPAN: RefCell<Option<EnteredSpan>> = RefCell::new(None);
+    static BATCH_SPAN: RefCell<Option<EnteredSpan>> = RefCell::new(None);
+}
+
+/// Enters the span of a slot, until [`exit_slot`].
+pub(crate) fn enter_slot(rollup_height: u64, da_height: u64) {
+    exit_slot();
+    let span = tracing::info_span!("slot", rollup_height, da_height).entered();
+    SLOT_SPAN.with(|slot| *slot.borrow_mut() = Some(span));
+}
+
+/// Enters the span of a batch, inside the span of its slot, until [`exit_batch`].
+pub(crate) fn enter_batch(batch_hash: [u8; 32], sequencer: &impl std::fmt::Display) {
+    exit_batch();
+    let span = tracing::info_span!(
+        "batch",
+        batch_hash = %hex_encode(&batch_hash),
+        %sequencer
+    )
+    .entered();
+    BATCH_SPAN.with(|batch| *batch.borrow_mut() = Some(span));
+}
+
+/// Exits the span of the current batch.
+pub(crate) fn exit_batch() {
+    BATCH_SPAN.with(|batch| batch.borrow_mut().take());
+}
+
+/// Exits the span of the current slot, and of its batch if it was not exited.
+pub(crate) fn exit_slot() {
+    exit_batch();
+    SLOT_SPAN.with(|slot| slot.borrow_mut().take());
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}